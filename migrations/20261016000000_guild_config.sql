@@ -0,0 +1,4 @@
+CREATE TABLE guild_config (
+    guild_id INTEGER PRIMARY KEY NOT NULL,
+    log_channel_id INTEGER
+);
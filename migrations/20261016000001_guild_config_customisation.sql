@@ -0,0 +1,4 @@
+ALTER TABLE guild_config ADD COLUMN webhook_name TEXT NOT NULL DEFAULT 'any message editor';
+ALTER TABLE guild_config ADD COLUMN edited_suffix TEXT NOT NULL DEFAULT 'edited by {editor}';
+ALTER TABLE guild_config ADD COLUMN editor_permission INTEGER NOT NULL DEFAULT 8192;
+ALTER TABLE guild_config ADD COLUMN error_message TEXT NOT NULL DEFAULT 'an error happened :( i let my developer know hopefully they''ll fix it soon!';
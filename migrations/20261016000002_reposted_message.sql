@@ -0,0 +1,7 @@
+CREATE TABLE reposted_message (
+    message_id INTEGER PRIMARY KEY NOT NULL,
+    author_id INTEGER NOT NULL,
+    original_content TEXT
+);
+
+ALTER TABLE guild_config ADD COLUMN undo_window INTEGER NOT NULL DEFAULT 60;
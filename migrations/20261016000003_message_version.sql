@@ -0,0 +1,13 @@
+ALTER TABLE reposted_message ADD COLUMN origin_id INTEGER NOT NULL DEFAULT 0;
+UPDATE reposted_message SET origin_id = message_id;
+
+CREATE TABLE message_version (
+    origin_id INTEGER NOT NULL,
+    author_id INTEGER NOT NULL,
+    editor_id INTEGER,
+    content TEXT NOT NULL,
+    reason TEXT,
+    timestamp INTEGER NOT NULL
+);
+
+CREATE INDEX message_version_origin_id ON message_version (origin_id);
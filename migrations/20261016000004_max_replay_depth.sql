@@ -0,0 +1 @@
+ALTER TABLE guild_config ADD COLUMN max_replay_depth INTEGER NOT NULL DEFAULT 25;
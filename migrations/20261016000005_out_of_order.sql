@@ -0,0 +1 @@
+ALTER TABLE guild_config ADD COLUMN out_of_order BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1 @@
+ALTER TABLE guild_config ADD COLUMN new_mentions BOOLEAN NOT NULL DEFAULT TRUE;
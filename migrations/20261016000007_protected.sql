@@ -0,0 +1,5 @@
+CREATE TABLE protected (
+    guild_id INTEGER NOT NULL,
+    id INTEGER NOT NULL,
+    PRIMARY KEY (guild_id, id)
+);
@@ -0,0 +1,5 @@
+CREATE TABLE opt_out (
+    user_id INTEGER NOT NULL,
+    guild_id INTEGER NOT NULL,
+    PRIMARY KEY (user_id, guild_id)
+);
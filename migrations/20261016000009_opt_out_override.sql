@@ -0,0 +1 @@
+ALTER TABLE opt_out ADD COLUMN opted_out BOOLEAN NOT NULL DEFAULT TRUE;
@@ -1,4 +1,8 @@
+pub mod config;
 pub mod edit;
+pub mod history;
+pub mod optout;
+pub mod undo;
 
 use std::{mem, ops::Deref};
 
@@ -9,43 +13,61 @@ use twilight_interactions::command::CreateCommand;
 use twilight_model::{
     application::{
         command::CommandType,
-        interaction::{modal::ModalSubmitInteraction, ApplicationCommand, Interaction},
+        component::Component,
+        interaction::{
+            message_component::MessageComponentInteraction, modal::ModalSubmitInteraction,
+            ApplicationCommand, Interaction,
+        },
     },
     channel::message::MessageFlags,
     guild::Permissions,
     http::interaction::{InteractionResponse, InteractionResponseType},
     id::{
-        marker::{ApplicationMarker, ChannelMarker, InteractionMarker},
+        marker::{ApplicationMarker, ChannelMarker, GuildMarker, InteractionMarker, UserMarker},
         Id,
     },
 };
 use twilight_util::builder::InteractionResponseDataBuilder;
 
-use crate::{Context, TEST_GUILD_ID};
+use crate::{storage::GuildConfig, Context, TEST_GUILD_ID};
 
 #[derive(Error, Debug)]
 enum Error {
     #[error("{0}")]
     Edit(#[from] edit::Error),
+    #[error("{0}")]
+    Config(#[from] config::Error),
+    #[error("{0}")]
+    Undo(#[from] undo::Error),
+    #[error("{0}")]
+    History(#[from] history::Error),
     #[error("please give me these permissions first:\n**{}**",
     format!("{:#?}", .0).to_lowercase().replace('_', " "))]
     SelfMissingPermissions(Permissions),
+    #[error("you need these permissions in this server to do that:\n**{}**",
+    format!("{:#?}", .0).to_lowercase().replace('_', " "))]
+    MissingPermissions(Permissions),
+    #[error("you need these permissions in this channel to do that:\n**{}**",
+    format!("{:#?}", .0).to_lowercase().replace('_', " "))]
+    MissingChannelPermissions(Permissions),
 }
 
 struct UpdateResponse<'res> {
     handler: &'res Handler<'res>,
     content: Option<&'res str>,
+    components: Option<&'res [Component]>,
 }
 
 impl<'res> UpdateResponse<'res> {
     async fn exec(&self) -> Result<(), anyhow::Error> {
-        self.handler
-            .http
-            .interaction(self.handler.application_id)
+        let interaction_client = self.handler.http.interaction(self.handler.application_id);
+        let mut update_response = interaction_client
             .update_response(&self.handler.token)
-            .content(self.content)?
-            .exec()
-            .await?;
+            .content(self.content)?;
+        if let Some(components) = self.components {
+            update_response = update_response.components(Some(components))?;
+        }
+        update_response.exec().await?;
 
         Ok(())
     }
@@ -54,11 +76,17 @@ impl<'res> UpdateResponse<'res> {
         self.content = Some(content);
         self
     }
+
+    const fn components(mut self, components: &'res [Component]) -> Self {
+        self.components = Some(components);
+        self
+    }
 }
 
 pub struct Handler<'ctx> {
     ctx: &'ctx Context,
     id: Id<InteractionMarker>,
+    guild_id: Option<Id<GuildMarker>>,
     token: String,
 }
 
@@ -76,13 +104,27 @@ impl<'ctx> Handler<'ctx> {
         ctx: &'ctx Context,
         interaction: &mut Interaction,
     ) -> Result<Handler<'ctx>, anyhow::Error> {
-        let (token, id) = match interaction {
-            Interaction::ApplicationCommand(cmd) => (mem::take(&mut cmd.token), cmd.id),
-            Interaction::ModalSubmit(modal) => (mem::take(&mut modal.token), modal.id),
+        let (token, id, guild_id) = match interaction {
+            Interaction::ApplicationCommand(cmd) => {
+                (mem::take(&mut cmd.token), cmd.id, cmd.guild_id)
+            }
+            Interaction::ModalSubmit(modal) => {
+                (mem::take(&mut modal.token), modal.id, modal.guild_id)
+            }
+            Interaction::MessageComponent(component) => (
+                mem::take(&mut component.token),
+                component.id,
+                component.guild_id,
+            ),
             _ => return Err(anyhow!("unknown interaction type: {interaction:#?}")),
         };
 
-        Ok(Self { ctx, id, token })
+        Ok(Self {
+            ctx,
+            id,
+            guild_id,
+            token,
+        })
     }
 
     #[allow(clippy::wildcard_enum_match_arm, clippy::option_if_let_else)]
@@ -90,20 +132,19 @@ impl<'ctx> Handler<'ctx> {
         if let Err(err) = match interaction {
             Interaction::ApplicationCommand(cmd) => self.handle_command(*cmd).await,
             Interaction::ModalSubmit(modal) => self.handle_modal_submit(*modal).await,
+            Interaction::MessageComponent(component) => self.handle_component(*component).await,
             _ => return Err(anyhow!("unknown interaction type: {interaction:#?}")),
         } {
             return if let Some(user_err) = err.downcast_ref::<Error>() {
                 self.update_response()
-                    .content(&user_err.to_string())
+                    .content(&user_err.to_string().chars().take(2000).collect::<String>())
                     .exec()
                     .await?;
                 Ok(())
             } else {
+                let error_message = self.error_message().await;
                 self.update_response()
-                    .content(
-                        "an error happened :( i let my developer know hopefully they'll fix it \
-                         soon!",
-                    )
+                    .content(&error_message)
                     .exec()
                     .await?;
                 Err(err)
@@ -120,6 +161,10 @@ impl<'ctx> Handler<'ctx> {
                 CommandType::ChatInput => self.edit().chat_input_command().await,
                 _ => Err(anyhow!("unknown command type: {command:#?}")),
             },
+            "undo edit" => self.undo().command(command).await,
+            "view edit history" => self.history().command(command).await,
+            "config" => self.config().command(command).await,
+            "optout" => self.optout().command(command).await,
             _ => Err(anyhow!("unknown command: {command:#?}")),
         }
     }
@@ -128,12 +173,34 @@ impl<'ctx> Handler<'ctx> {
         &self,
         modal: ModalSubmitInteraction,
     ) -> Result<(), anyhow::Error> {
-        match modal.data.custom_id.as_str() {
+        match modal
+            .data
+            .custom_id
+            .split_once(':')
+            .map_or(modal.data.custom_id.as_str(), |(name, _)| name)
+        {
             "edit_modal" => self.edit().modal_submit(modal).await,
             _ => Err(anyhow!("unknown modal: {modal:#?}")),
         }
     }
 
+    async fn handle_component(
+        &self,
+        component: MessageComponentInteraction,
+    ) -> Result<(), anyhow::Error> {
+        match component
+            .data
+            .custom_id
+            .split_once(':')
+            .map_or(component.data.custom_id.as_str(), |(name, _)| name)
+        {
+            "edit_history" => self.history().component(component).await,
+            "edit_confirm" => self.edit().confirm(component).await,
+            "edit_cancel" => self.edit().cancel(component).await,
+            _ => Err(anyhow!("unknown component: {component:#?}")),
+        }
+    }
+
     #[allow(clippy::wildcard_enum_match_arm)]
     async fn defer(&self) -> Result<(), anyhow::Error> {
         self.create_response(&InteractionResponse {
@@ -149,10 +216,21 @@ impl<'ctx> Handler<'ctx> {
         Ok(())
     }
 
+    async fn defer_update(&self) -> Result<(), anyhow::Error> {
+        self.create_response(&InteractionResponse {
+            kind: InteractionResponseType::DeferredUpdateMessage,
+            data: None,
+        })
+        .await?;
+
+        Ok(())
+    }
+
     const fn update_response(&self) -> UpdateResponse<'_> {
         UpdateResponse {
             handler: self,
             content: None,
+            components: None,
         }
     }
 
@@ -184,9 +262,66 @@ impl<'ctx> Handler<'ctx> {
         }
     }
 
+    fn check_permissions(
+        &self,
+        user_id: Id<UserMarker>,
+        guild_id: Id<GuildMarker>,
+        required: Permissions,
+    ) -> Result<(), anyhow::Error> {
+        let missing_permissions = required - self.cache.permissions().root(user_id, guild_id)?;
+
+        if missing_permissions.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::MissingPermissions(missing_permissions).into())
+        }
+    }
+
+    fn check_channel_permissions(
+        &self,
+        user_id: Id<UserMarker>,
+        channel_id: Id<ChannelMarker>,
+        required: Permissions,
+    ) -> Result<(), anyhow::Error> {
+        let missing_permissions =
+            required - self.cache.permissions().in_channel(user_id, channel_id)?;
+
+        if missing_permissions.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::MissingChannelPermissions(missing_permissions).into())
+        }
+    }
+
+    async fn error_message(&self) -> String {
+        if let Some(guild_id) = self.guild_id {
+            if let Ok(config) = self.storage.guild_config(guild_id).await {
+                return config.error_message;
+            }
+        }
+
+        GuildConfig::default().error_message
+    }
+
     pub const fn edit(&self) -> edit::Handler {
         edit::Handler::new(self)
     }
+
+    pub const fn undo(&self) -> undo::Handler<'_> {
+        undo::Handler::new(self)
+    }
+
+    pub const fn history(&self) -> history::Handler<'_> {
+        history::Handler::new(self)
+    }
+
+    pub const fn config(&self) -> config::Handler<'_> {
+        config::Handler::new(self)
+    }
+
+    pub const fn optout(&self) -> optout::Handler<'_> {
+        optout::Handler::new(self)
+    }
 }
 
 pub async fn create_commands(
@@ -194,7 +329,14 @@ pub async fn create_commands(
     application_id: Id<ApplicationMarker>,
 ) -> Result<(), anyhow::Error> {
     let interaction_client = http.interaction(application_id);
-    let commands = [edit::build(), edit::ChatInput::create_command().into()];
+    let commands = [
+        edit::build(),
+        edit::ChatInput::create_command().into(),
+        undo::build(),
+        history::build(),
+        config::ChatInput::create_command().into(),
+        optout::ChatInput::create_command().into(),
+    ];
 
     interaction_client
         .set_guild_commands(TEST_GUILD_ID, &commands)
@@ -0,0 +1,432 @@
+use std::ops::Deref;
+
+use anyhow::Context;
+use thiserror::Error;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_model::{
+    application::interaction::{application_command::InteractionChannel, ApplicationCommand},
+    guild::Permissions,
+    id::{
+        marker::{GuildMarker, RoleMarker, UserMarker},
+        Id,
+    },
+};
+
+use crate::{
+    interaction::{self, edit},
+    storage::GuildConfig,
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("that's too long, it can be {0} characters at most")]
+    TooLong(usize),
+    #[error(
+        "that's too long to fit in a name, it can be {0} characters at most with `{{editor}}` \
+         counting as {1}"
+    )]
+    SuffixTooLong(usize, usize),
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "config",
+    desc = "change how i work in this server",
+    default_permissions = "permissions",
+    dm_permission = false
+)]
+pub enum ChatInput {
+    #[command(name = "view")]
+    View(View),
+    #[command(name = "log-channel")]
+    LogChannel(LogChannel),
+    #[command(name = "webhook-name")]
+    WebhookName(WebhookName),
+    #[command(name = "edited-suffix")]
+    EditedSuffix(EditedSuffix),
+    #[command(name = "editor-permission")]
+    EditorPermission(EditorPermission),
+    #[command(name = "error-message")]
+    ErrorMessage(ErrorMessage),
+    #[command(name = "undo-window")]
+    UndoWindow(UndoWindow),
+    #[command(name = "max-replay-depth")]
+    MaxReplayDepth(MaxReplayDepth),
+    #[command(name = "edit-mode")]
+    EditMode(EditMode),
+    #[command(name = "new-mentions")]
+    NewMentions(NewMentions),
+    #[command(name = "protect-role")]
+    ProtectRole(ProtectRole),
+    #[command(name = "protect-user")]
+    ProtectUser(ProtectUser),
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(name = "view", desc = "see the current config for this server")]
+pub struct View {}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "log-channel",
+    desc = "set the channel i post every edit to, leave it empty to stop posting them"
+)]
+pub struct LogChannel {
+    /// the channel to post the edits to
+    #[command(channel_types = "guild_text guild_news")]
+    channel: Option<InteractionChannel>,
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "webhook-name",
+    desc = "set the name of the webhooks i create, existing webhooks keep their name"
+)]
+pub struct WebhookName {
+    /// the name to give the webhooks
+    name: String,
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "edited-suffix",
+    desc = "set what's added to the edited message's name, {editor} is replaced with the editor"
+)]
+pub struct EditedSuffix {
+    /// the text to add, like `edited by {editor}`
+    suffix: String,
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "editor-permission",
+    desc = "set the permission members need to edit messages"
+)]
+pub struct EditorPermission {
+    /// the permission members need
+    permission: Permission,
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "error-message",
+    desc = "set the message i reply with when something unexpected goes wrong"
+)]
+pub struct ErrorMessage {
+    /// the message to reply with
+    message: String,
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "undo-window",
+    desc = "set how long edits can be undone for, set it to 0 to turn undoing off"
+)]
+pub struct UndoWindow {
+    /// how many minutes after an edit it can be undone
+    #[command(min_value = 0, max_value = 10080)]
+    minutes: i64,
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "max-replay-depth",
+    desc = "set how many messages i can re-post at most to edit a message"
+)]
+pub struct MaxReplayDepth {
+    /// the most messages i can re-post, including the edited one
+    #[command(min_value = 1, max_value = 100)]
+    messages: i64,
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "edit-mode",
+    desc = "set whether the messages sent after the edited one are re-posted by default"
+)]
+pub struct EditMode {
+    /// what to re-post when editing, it can still be changed for each edit
+    mode: Mode,
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "new-mentions",
+    desc = "set whether edits can ping people and roles the original message didn't ping"
+)]
+pub struct NewMentions {
+    /// if on, editors can still only ping what they could ping themselves
+    allow: bool,
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "protect-role",
+    desc = "set whether messages by members with a role can be edited"
+)]
+pub struct ProtectRole {
+    /// the role to protect or unprotect
+    role: Id<RoleMarker>,
+    /// if on, nobody but the server owner can edit messages by members with this role
+    protected: bool,
+}
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "protect-user",
+    desc = "set whether messages by a member can be edited"
+)]
+pub struct ProtectUser {
+    /// the member to protect or unprotect
+    user: Id<UserMarker>,
+    /// if on, nobody but the server owner can edit this member's messages
+    protected: bool,
+}
+
+#[derive(CommandOption, CreateOption)]
+pub enum Mode {
+    #[option(name = "re-post the messages after it too", value = "replay")]
+    Replay,
+    #[option(
+        name = "only re-post the edited message at the bottom",
+        value = "out_of_order"
+    )]
+    OutOfOrder,
+}
+
+#[derive(CommandOption, CreateOption)]
+pub enum Permission {
+    #[option(name = "manage messages", value = "manage_messages")]
+    ManageMessages,
+    #[option(name = "moderate members", value = "moderate_members")]
+    ModerateMembers,
+    #[option(name = "manage server", value = "manage_guild")]
+    ManageGuild,
+    #[option(name = "administrator", value = "administrator")]
+    Administrator,
+}
+
+impl From<Permission> for Permissions {
+    fn from(permission: Permission) -> Self {
+        match permission {
+            Permission::ManageMessages => Self::MANAGE_MESSAGES,
+            Permission::ModerateMembers => Self::MODERATE_MEMBERS,
+            Permission::ManageGuild => Self::MANAGE_GUILD,
+            Permission::Administrator => Self::ADMINISTRATOR,
+        }
+    }
+}
+
+const fn permissions() -> Permissions {
+    Permissions::MANAGE_GUILD
+}
+
+pub struct Handler<'ctx>(&'ctx interaction::Handler<'ctx>);
+
+impl<'ctx> Deref for Handler<'ctx> {
+    type Target = interaction::Handler<'ctx>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<'ctx> Handler<'ctx> {
+    pub const fn new(interaction_handler: &'ctx interaction::Handler) -> Self {
+        Self(interaction_handler)
+    }
+
+    pub async fn command(&self, command: ApplicationCommand) -> Result<(), anyhow::Error> {
+        self.defer().await?;
+
+        let guild_id = command
+            .guild_id
+            .context("config command isn't run in a guild")?;
+        let mut config = self.storage.guild_config(guild_id).await?;
+
+        let reply = match ChatInput::from_interaction(command.data.into())? {
+            ChatInput::View(_) => view(&config, &self.protected(guild_id).await?),
+            ChatInput::LogChannel(options) => {
+                if let Some(channel) = options.channel {
+                    self.check_self_permissions(
+                        channel.id,
+                        Permissions::VIEW_CHANNEL
+                            | Permissions::SEND_MESSAGES
+                            | Permissions::EMBED_LINKS,
+                    )?;
+                    config.log_channel_id = Some(channel.id);
+                    format!("done! i'll post every edit to <#{}>", channel.id)
+                } else {
+                    config.log_channel_id = None;
+                    "done! i won't post the edits anywhere anymore".to_owned()
+                }
+            }
+            ChatInput::WebhookName(options) => {
+                check_length(&options.name, 80)?;
+                config.webhook_name = options.name;
+                "done! i'll use this name for the webhooks i create from now on".to_owned()
+            }
+            ChatInput::EditedSuffix(options) => {
+                check_length(&options.suffix, 40)?;
+                if options
+                    .suffix
+                    .replace("{editor}", &"_".repeat(edit::NAME_MAX_CHARS))
+                    .chars()
+                    .count()
+                    > edit::EDITED_SUFFIX_MAX_CHARS
+                {
+                    return Err(super::Error::Config(Error::SuffixTooLong(
+                        edit::EDITED_SUFFIX_MAX_CHARS,
+                        edit::NAME_MAX_CHARS,
+                    ))
+                    .into());
+                }
+                config.edited_suffix = options.suffix;
+                "done! i'll add this to the name of the messages edited from now on".to_owned()
+            }
+            ChatInput::EditorPermission(options) => {
+                config.editor_permission = options.permission.into();
+                "done! members with this permission can edit messages now, make sure they can \
+                 also see the edit command in the integrations settings"
+                    .to_owned()
+            }
+            ChatInput::ErrorMessage(options) => {
+                check_length(&options.message, 1000)?;
+                config.error_message = options.message;
+                "done! i'll reply with this when something goes wrong".to_owned()
+            }
+            ChatInput::UndoWindow(options) => {
+                config.undo_window = u32::try_from(options.minutes)?;
+                if config.undo_window == 0 {
+                    "done! edits can't be undone anymore".to_owned()
+                } else {
+                    format!(
+                        "done! edits can be undone for {} minutes from now on",
+                        config.undo_window
+                    )
+                }
+            }
+            ChatInput::MaxReplayDepth(options) => {
+                config.max_replay_depth = u32::try_from(options.messages)?;
+                format!(
+                    "done! i'll re-post {} messages at most to edit a message",
+                    config.max_replay_depth
+                )
+            }
+            ChatInput::EditMode(options) => {
+                config.out_of_order = matches!(options.mode, Mode::OutOfOrder);
+                if config.out_of_order {
+                    "done! i'll only re-post the edited message at the bottom of the channel by \
+                     default from now on"
+                        .to_owned()
+                } else {
+                    "done! i'll re-post the messages sent after the edited one too by default \
+                     from now on"
+                        .to_owned()
+                }
+            }
+            ChatInput::NewMentions(options) => {
+                config.new_mentions = options.allow;
+                if config.new_mentions {
+                    "done! edits can ping what the editor could ping themselves from now on"
+                        .to_owned()
+                } else {
+                    "done! edits can only ping what the original message pinged from now on"
+                        .to_owned()
+                }
+            }
+            ChatInput::ProtectRole(options) => {
+                self.storage
+                    .set_protected(guild_id, options.role.cast(), options.protected)
+                    .await?;
+                if options.protected {
+                    format!(
+                        "done! only the server owner can edit messages by members with <@&{}> now",
+                        options.role
+                    )
+                } else {
+                    format!(
+                        "done! messages by members with <@&{}> can be edited again",
+                        options.role
+                    )
+                }
+            }
+            ChatInput::ProtectUser(options) => {
+                self.storage
+                    .set_protected(guild_id, options.user.cast(), options.protected)
+                    .await?;
+                if options.protected {
+                    format!(
+                        "done! only the server owner can edit messages by <@{}> now",
+                        options.user
+                    )
+                } else {
+                    format!("done! messages by <@{}> can be edited again", options.user)
+                }
+            }
+        };
+
+        self.storage.set_guild_config(guild_id, &config).await?;
+
+        self.update_response().content(&reply).exec().await
+    }
+
+    async fn protected(&self, guild_id: Id<GuildMarker>) -> Result<String, anyhow::Error> {
+        let mentions: Vec<_> = self
+            .storage
+            .protected_ids(guild_id)
+            .await?
+            .into_iter()
+            .map(|id| {
+                if self.cache.role(id.cast()).is_some() {
+                    format!("<@&{id}>")
+                } else {
+                    format!("<@{id}>")
+                }
+            })
+            .collect();
+
+        Ok(if mentions.is_empty() {
+            "none".to_owned()
+        } else {
+            mentions.join(", ")
+        })
+    }
+}
+
+fn check_length(value: &str, max_chars: usize) -> Result<(), anyhow::Error> {
+    if value.chars().count() > max_chars {
+        Err(super::Error::Config(Error::TooLong(max_chars)).into())
+    } else {
+        Ok(())
+    }
+}
+
+fn view(config: &GuildConfig, protected: &str) -> String {
+    format!(
+        "**log channel:** {}\n**webhook name:** {}\n**edited suffix:** {}\n**editor permission:** \
+         {}\n**error message:** {}\n**undo window:** {} minutes\n**max replay depth:** {} \
+         messages\n**edit mode:** {}\n**new mentions:** {}\n**protected:** {protected}",
+        config
+            .log_channel_id
+            .map_or_else(|| "none".to_owned(), |id| format!("<#{id}>")),
+        config.webhook_name,
+        config.edited_suffix,
+        format!("{:#?}", config.editor_permission)
+            .to_lowercase()
+            .replace('_', " "),
+        config.error_message,
+        config.undo_window,
+        config.max_replay_depth,
+        if config.out_of_order {
+            "only re-post the edited message"
+        } else {
+            "re-post the messages after it too"
+        },
+        if config.new_mentions {
+            "allowed"
+        } else {
+            "not allowed"
+        }
+    )
+}
@@ -1,39 +1,141 @@
-use std::{fmt::Write, ops::Deref};
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    mem,
+    ops::Deref,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::Context;
+use dashmap::{
+    mapref::{entry::Entry, one::Ref},
+    DashMap,
+};
+use serde::Deserialize;
+use similar::{ChangeTag, TextDiff};
 use thiserror::Error;
-use twilight_cache_inmemory::{model::CachedMessage, Reference};
+use twilight_cache_inmemory::model::{CachedMember, CachedMessage};
+use twilight_http::request::{channel::reaction::RequestReactionType, AuditLogReason};
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_model::{
     application::{
         command::{Command, CommandType},
-        component::{text_input::TextInputStyle, ActionRow, Component, TextInput},
-        interaction::{modal::ModalSubmitInteraction, ApplicationCommand},
+        component::{
+            button::ButtonStyle, text_input::TextInputStyle, ActionRow, Button, Component,
+            TextInput,
+        },
+        interaction::{
+            message_component::MessageComponentInteraction, modal::ModalSubmitInteraction,
+            ApplicationCommand,
+        },
     },
     channel::{
-        message::{MessageFlags, MessageType},
-        Message,
+        embed::Embed,
+        message::{allowed_mentions::ParseTypes, AllowedMentions, MessageFlags, MessageType},
+        Attachment, ChannelType, Message, ReactionType, Webhook,
+    },
+    guild::{Permissions, PremiumTier},
+    http::{
+        attachment::Attachment as HttpAttachment,
+        interaction::{InteractionResponse, InteractionResponseType},
+    },
+    id::{
+        marker::{
+            ChannelMarker, GuildMarker, InteractionMarker, MessageMarker, RoleMarker, UserMarker,
+        },
+        Id,
     },
-    guild::Permissions,
-    http::interaction::{InteractionResponse, InteractionResponseType},
-    id::{marker::MessageMarker, Id},
+    user::User,
+};
+use twilight_util::{
+    builder::{
+        command::CommandBuilder,
+        embed::{EmbedBuilder, EmbedFieldBuilder},
+        InteractionResponseDataBuilder,
+    },
+    snowflake::Snowflake,
 };
-use twilight_util::builder::{command::CommandBuilder, InteractionResponseDataBuilder};
 use twilight_webhook::util::{MinimalMember, MinimalWebhook};
 
-use crate::interaction;
+use crate::{
+    interaction,
+    storage::{MessageVersion, RepostedMessage},
+};
+
+type ReplayMessage = (CachedMessage, Id<UserMarker>, Id<MessageMarker>);
+
+const BULK_DELETE_MAX_AGE: i64 = 14 * 24 * 60 * 60 - 60 * 60;
+
+const USERNAME_MAX_CHARS: usize = 80;
+
+pub const NAME_MAX_CHARS: usize = 32;
+
+pub const EDITED_SUFFIX_MAX_CHARS: usize = USERNAME_MAX_CHARS - NAME_MAX_CHARS - " ()".len();
 
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("this message is weird, it has something i cant recreate like a reaction.. sorry")]
+    #[error("this message is weird, it has something i cant recreate like a sticker.. sorry")]
     MessageWeird,
     #[error("this message is too long, someone with nitro sent it but bots dont have nitro sadly")]
     MessageTooLong,
+    #[error("this message is by the owner of the server, nobody can edit their messages")]
+    AuthorOwner,
+    #[error("the author of this message opted out of having their messages edited with me")]
+    AuthorOptedOut,
+    #[error(
+        "the author of this message is protected in this server, their messages can't be edited"
+    )]
+    AuthorProtected,
+    #[error(
+        "the author of this message has a role as high as yours or higher, you can only edit \
+         messages by members below you"
+    )]
+    AuthorHigherRole,
     #[error(
-        "i dont know any messages here yet, i can only see messages sent after i joined or got \
-         updated.. sorry"
+        "i can't edit messages in stage channels because discord doesn't let me post as others \
+         there.. sorry"
     )]
-    NoCachedMessages,
+    StageChannel,
+    #[error(
+        "i can't edit messages in voice channels because discord doesn't give me the permissions \
+         i need there.. sorry"
+    )]
+    VoiceChannel,
+    #[error("i can't edit messages in this kind of channel.. sorry")]
+    UnsupportedChannel,
+    #[error(
+        "this is the first message of a forum post, i can't re-post it without deleting the \
+         whole post.. sorry, you can still edit the replies to it"
+    )]
+    ForumPostStarter,
+    #[error(
+        "there are too many messages sent after this one, i can only re-post {0} messages at \
+         most here"
+    )]
+    TooManyMessages(u32),
+    #[error(
+        "someone else is editing a message here right now, please try again when they're done"
+    )]
+    ChannelBusy,
+    #[error("this edit expired, please try editing the message again")]
+    ConfirmationExpired,
+    #[error("something went wrong while re-posting the messages, nothing was changed")]
+    RepostFailed,
+    #[error(
+        "something went wrong while re-posting the messages, so i deleted the {0} messages i \
+         already re-posted and nothing was changed"
+    )]
+    RolledBack(usize),
+    #[error(
+        "something went wrong while re-posting the messages and i couldn't delete the ones i \
+         already re-posted, you might want to delete them yourself:{0}"
+    )]
+    RollbackFailed(String),
+    #[error(
+        "i re-posted the messages but something went wrong while deleting the original ones, you \
+         might want to delete the duplicates yourself"
+    )]
+    DeleteFailed,
 }
 
 #[derive(CreateCommand, CommandModel)]
@@ -42,6 +144,70 @@ pub struct ChatInput {}
 
 pub struct Handler<'ctx>(&'ctx interaction::Handler<'ctx>);
 
+pub struct Pending {
+    channel_id: Id<ChannelMarker>,
+    guild_id: Id<GuildMarker>,
+    message_id: Id<MessageMarker>,
+    content: String,
+    reason: Option<String>,
+    mark_edited: bool,
+    out_of_order: bool,
+    editor_id: Id<UserMarker>,
+    editor_name: String,
+    new_mentions: AllowedMentions,
+}
+
+pub struct Replay<'a> {
+    pub channel_id: Id<ChannelMarker>,
+    pub guild_id: Id<GuildMarker>,
+    pub target_id: Id<MessageMarker>,
+    pub target_content: &'a str,
+    pub edited_suffix: Option<&'a str>,
+    pub reason: Option<&'a str>,
+    pub audit_reason: &'a str,
+    pub out_of_order: bool,
+    pub undoable: bool,
+    pub new_mentions: &'a AllowedMentions,
+}
+
+pub struct Replayed {
+    pub skipped_weird: bool,
+    pub left_behind: usize,
+    pub reactions_report: Vec<String>,
+    pub target: Option<ReplayedTarget>,
+}
+
+pub struct ReplayedTarget {
+    pub message_id: Id<MessageMarker>,
+    pub author_id: Id<UserMarker>,
+    pub origin_id: Id<MessageMarker>,
+    pub original_content: String,
+    pub original_timestamp: i64,
+    pub timestamp: i64,
+    pub mentions_neutralised: bool,
+}
+
+#[derive(Deserialize)]
+struct GlobalName {
+    global_name: Option<String>,
+}
+
+struct ChannelLock<'a> {
+    replaying_channels: &'a DashMap<Id<ChannelMarker>, Vec<Id<MessageMarker>>>,
+    channel_id: Id<ChannelMarker>,
+}
+
+struct EditLog<'a> {
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    message_id: Id<MessageMarker>,
+    author_id: Id<UserMarker>,
+    editor_id: Id<UserMarker>,
+    original_content: &'a str,
+    edited_content: &'a str,
+    reason: Option<&'a str>,
+}
+
 impl<'ctx> Deref for Handler<'ctx> {
     type Target = interaction::Handler<'ctx>;
 
@@ -83,11 +249,26 @@ impl<'ctx> Handler<'ctx> {
     }
 
     async fn _command(&self, command: ApplicationCommand) -> Result<(), anyhow::Error> {
+        self.check_channel(command.channel_id)?;
+
         self.check_self_permissions(
             command.channel_id,
-            Permissions::MANAGE_MESSAGES | Permissions::MANAGE_WEBHOOKS,
+            Permissions::MANAGE_MESSAGES
+                | Permissions::MANAGE_WEBHOOKS
+                | Permissions::READ_MESSAGE_HISTORY,
         )?;
 
+        let guild_id = command.guild_id.context("command isn't run in a guild")?;
+        let config = self.storage.guild_config(guild_id).await?;
+        let editor_id = command
+            .member
+            .as_ref()
+            .and_then(|member| member.user.as_ref())
+            .context("command member doesn't include user info")?
+            .id;
+        self.check_permissions(editor_id, guild_id, config.editor_permission)?;
+        self.check_channel_permissions(editor_id, command.channel_id, config.editor_permission)?;
+
         let message = command
             .data
             .resolved
@@ -97,13 +278,25 @@ impl<'ctx> Handler<'ctx> {
             .next()
             .context("command data doesn't have a message")?;
 
-        if self.cache.message(message.id).is_none() {
-            return Err(super::Error::Edit(Error::NoCachedMessages).into());
-        };
+        let reposted = self.storage.reposted_message(message.id).await?;
+        let is_reposted = reposted.is_some();
+        self.check_author(
+            guild_id,
+            editor_id,
+            reposted.map_or(message.author.id, |reposted_message| {
+                reposted_message.author_id
+            }),
+        )
+        .await?;
+        if is_forum_post_starter(message.id, message.channel_id) {
+            return Err(super::Error::Edit(Error::ForumPostStarter).into());
+        }
         if message.content.chars().count() > 2000 {
             return Err(super::Error::Edit(Error::MessageTooLong).into());
         }
-        if message_is_weird(&message) {
+        if message_is_weird(&message)
+            || !is_reposted && (message.author.bot || message.webhook_id.is_some())
+        {
             return Err(super::Error::Edit(Error::MessageWeird).into());
         }
 
@@ -112,36 +305,939 @@ impl<'ctx> Handler<'ctx> {
             data: Some(
                 InteractionResponseDataBuilder::new()
                     .title("edit message".to_owned())
-                    .custom_id("edit_modal".to_owned())
-                    .components([Component::ActionRow(ActionRow {
-                        components: vec![Component::TextInput(TextInput {
-                            custom_id: message.id.to_string(),
-                            label: "what to edit the message to".to_owned(),
-                            style: TextInputStyle::Paragraph,
-                            value: Some(message.content),
-                            max_length: Some(2000),
-                            min_length: None,
-                            placeholder: None,
-                            required: None,
-                        })],
-                    })])
+                    .custom_id(format!("edit_modal:{}", message.id))
+                    .components([
+                        Component::ActionRow(ActionRow {
+                            components: vec![Component::TextInput(TextInput {
+                                custom_id: "content".to_owned(),
+                                label: "what to edit the message to".to_owned(),
+                                style: TextInputStyle::Paragraph,
+                                value: Some(message.content),
+                                max_length: Some(2000),
+                                min_length: None,
+                                placeholder: None,
+                                required: None,
+                            })],
+                        }),
+                        Component::ActionRow(ActionRow {
+                            components: vec![Component::TextInput(TextInput {
+                                custom_id: "reason".to_owned(),
+                                label: "why are you editing it".to_owned(),
+                                style: TextInputStyle::Short,
+                                value: None,
+                                max_length: Some(100),
+                                min_length: None,
+                                placeholder: Some("optional".to_owned()),
+                                required: Some(false),
+                            })],
+                        }),
+                        Component::ActionRow(ActionRow {
+                            components: vec![Component::TextInput(TextInput {
+                                custom_id: "mark_edited".to_owned(),
+                                label: "show who edited it? (yes/no)".to_owned(),
+                                style: TextInputStyle::Short,
+                                value: Some("yes".to_owned()),
+                                max_length: Some(3),
+                                min_length: Some(1),
+                                placeholder: None,
+                                required: Some(true),
+                            })],
+                        }),
+                        Component::ActionRow(ActionRow {
+                            components: vec![Component::TextInput(TextInput {
+                                custom_id: "replay_later".to_owned(),
+                                label: "re-post the messages sent after it? (yes/no)".to_owned(),
+                                style: TextInputStyle::Short,
+                                value: Some(
+                                    if config.out_of_order { "no" } else { "yes" }.to_owned(),
+                                ),
+                                max_length: Some(3),
+                                min_length: Some(1),
+                                placeholder: None,
+                                required: Some(true),
+                            })],
+                        }),
+                    ])
                     .build(),
             ),
         })
         .await
     }
 
-    pub async fn modal_submit(
+    pub async fn modal_submit(&self, modal: ModalSubmitInteraction) -> Result<(), anyhow::Error> {
+        self.defer().await?;
+
+        let edit_message_id: Id<MessageMarker> = modal
+            .data
+            .custom_id
+            .split_once(':')
+            .context("modal custom id doesn't have the message id")?
+            .1
+            .parse()?;
+        let mut inputs: HashMap<_, _> = modal
+            .data
+            .components
+            .into_iter()
+            .flat_map(|row| row.components)
+            .map(|input| (input.custom_id, input.value))
+            .collect();
+        let content = inputs
+            .remove("content")
+            .context("modal doesn't have the content input")?;
+        let reason = inputs
+            .remove("reason")
+            .filter(|reason| !reason.trim().is_empty());
+        let mark_edited = !answered_no(
+            &inputs
+                .remove("mark_edited")
+                .context("modal doesn't have the mark edited input")?,
+        );
+        let out_of_order = answered_no(
+            &inputs
+                .remove("replay_later")
+                .context("modal doesn't have the replay later input")?,
+        );
+
+        let guild_id = modal
+            .guild_id
+            .context("modal interaction doesn't have a guild id")?;
+        let interaction_member = modal
+            .member
+            .as_ref()
+            .context("modal interaction doesn't have a member")?;
+        let editor = interaction_member
+            .user
+            .as_ref()
+            .context("modal interaction member doesn't include user info")?;
+
+        self.check_channel_permissions(
+            editor.id,
+            modal.channel_id,
+            self.storage.guild_config(guild_id).await?.editor_permission,
+        )?;
+
+        let reposted = self.storage.reposted_message(edit_message_id).await?;
+        let author_id = match &reposted {
+            Some(reposted_message) => reposted_message.author_id,
+            None => self
+                .message(modal.channel_id, edit_message_id)
+                .await?
+                .author(),
+        };
+        self.check_author(guild_id, editor.id, author_id).await?;
+
+        let in_place = if reposted.is_some() {
+            let webhook_id = self.webhook(modal.channel_id, guild_id).await?.0.id;
+            self.message(modal.channel_id, edit_message_id)
+                .await?
+                .webhook_id()
+                == Some(webhook_id)
+        } else {
+            false
+        };
+
+        let new_mentions = self
+            .new_mentions(&content, editor.id, modal.channel_id, guild_id)
+            .await?;
+        let pending = Pending {
+            channel_id: modal.channel_id,
+            guild_id,
+            message_id: edit_message_id,
+            content,
+            reason,
+            mark_edited,
+            out_of_order,
+            editor_id: editor.id,
+            editor_name: self
+                .display_name(interaction_member.nick.as_deref(), editor)
+                .await,
+            new_mentions,
+        };
+
+        if !out_of_order && !in_place {
+            let max_replay_depth = self.storage.guild_config(guild_id).await?.max_replay_depth;
+            let replay_count = self
+                .replay_messages(modal.channel_id, edit_message_id, false, max_replay_depth)
+                .await?
+                .0
+                .len();
+            if replay_count > 1 {
+                self.pending_edits.retain(|id, _| {
+                    id.timestamp() > (unix_timestamp().unwrap_or_default() - 15 * 60) * 1000
+                });
+                self.pending_edits.insert(modal.id, pending);
+
+                return self
+                    .update_response()
+                    .content(&format!(
+                        "this will re-post {replay_count} messages, the message to edit and the \
+                         ones sent after it, are you sure?"
+                    ))
+                    .components(&[Component::ActionRow(ActionRow {
+                        components: vec![
+                            confirmation_button(
+                                ("edit_confirm", "edit it"),
+                                modal.id,
+                                ButtonStyle::Danger,
+                            ),
+                            confirmation_button(
+                                ("edit_cancel", "cancel"),
+                                modal.id,
+                                ButtonStyle::Secondary,
+                            ),
+                        ],
+                    })])
+                    .exec()
+                    .await;
+            }
+        }
+
+        let response = Box::pin(self.apply(&pending)).await?;
+        self.update_response().content(&response).exec().await
+    }
+
+    pub async fn confirm(
         &self,
-        mut modal: ModalSubmitInteraction,
+        component: MessageComponentInteraction,
     ) -> Result<(), anyhow::Error> {
-        self.defer().await?;
+        self.defer_update().await?;
+
+        let (_, pending) = self
+            .pending_edits
+            .remove(&pending_edit_id(&component.data.custom_id)?)
+            .ok_or(super::Error::Edit(Error::ConfirmationExpired))?;
+
+        self.check_channel_permissions(
+            pending.editor_id,
+            pending.channel_id,
+            self.storage
+                .guild_config(pending.guild_id)
+                .await?
+                .editor_permission,
+        )?;
+        let author_id = match self.storage.reposted_message(pending.message_id).await? {
+            Some(reposted_message) => reposted_message.author_id,
+            None => self
+                .message(pending.channel_id, pending.message_id)
+                .await?
+                .author(),
+        };
+        self.check_author(pending.guild_id, pending.editor_id, author_id)
+            .await?;
+
+        let response = Box::pin(self.apply(&pending)).await?;
+
+        self.update_response()
+            .content(&response)
+            .components(&[])
+            .exec()
+            .await
+    }
+
+    pub async fn cancel(
+        &self,
+        component: MessageComponentInteraction,
+    ) -> Result<(), anyhow::Error> {
+        self.defer_update().await?;
+
+        self.pending_edits
+            .remove(&pending_edit_id(&component.data.custom_id)?);
+
+        self.update_response()
+            .content("okay, i didn't edit anything")
+            .components(&[])
+            .exec()
+            .await
+    }
+
+    async fn apply(&self, pending: &Pending) -> Result<String, anyhow::Error> {
+        let config = self.storage.guild_config(pending.guild_id).await?;
+        let audit_reason = pending.reason.as_ref().map_or_else(
+            || format!("edited by {}", pending.editor_name),
+            |edit_reason| format!("edited by {}: {edit_reason}", pending.editor_name),
+        );
+        let edited_suffix = config
+            .edited_suffix
+            .replace("{editor}", &pending.editor_name);
+
+        let undoable = config.undo_window > 0;
+        let in_place_target = match self.storage.reposted_message(pending.message_id).await? {
+            Some(reposted) => {
+                self.edit_in_place(
+                    pending.channel_id,
+                    pending.guild_id,
+                    (pending.message_id, reposted),
+                    (&pending.content, &pending.new_mentions),
+                    undoable,
+                )
+                .await?
+            }
+            None => None,
+        };
+        let in_place = in_place_target.is_some();
+        let replayed = if let Some(target) = in_place_target {
+            Replayed {
+                skipped_weird: false,
+                left_behind: 0,
+                reactions_report: Vec::new(),
+                target: Some(target),
+            }
+        } else {
+            Box::pin(self.replay(&Replay {
+                channel_id: pending.channel_id,
+                guild_id: pending.guild_id,
+                target_id: pending.message_id,
+                target_content: &pending.content,
+                edited_suffix: pending.mark_edited.then_some(edited_suffix.as_str()),
+                reason: pending.reason.as_deref(),
+                audit_reason: &audit_reason,
+                out_of_order: pending.out_of_order,
+                undoable,
+                new_mentions: &pending.new_mentions,
+            }))
+            .await?
+        };
+
+        if let Some(target) = &replayed.target {
+            self.add_version(
+                target,
+                pending.editor_id,
+                &pending.content,
+                pending.reason.as_deref(),
+            )
+            .await?;
+        }
+
+        let mut response = "done!".to_owned();
+
+        if let (Some(log_channel_id), Some(target)) = (config.log_channel_id, &replayed.target) {
+            let log = EditLog {
+                guild_id: pending.guild_id,
+                channel_id: pending.channel_id,
+                message_id: target.message_id,
+                author_id: target.author_id,
+                editor_id: pending.editor_id,
+                original_content: &target.original_content,
+                edited_content: &pending.content,
+                reason: pending.reason.as_deref(),
+            };
+            if self.log_edit(log_channel_id, &log).await.is_err() {
+                response.push_str(
+                    "\n\ni couldn't post the edit to the log channel, make sure i can send \
+                     messages and embeds there",
+                );
+            }
+        }
+
+        if in_place && (pending.mark_edited || pending.out_of_order) {
+            response.push_str(
+                "\n\ni sent this message so i edited it without re-posting, that means i couldn't ",
+            );
+            response.push_str(match (pending.mark_edited, pending.out_of_order) {
+                (true, true) => {
+                    "change its name to show who edited it or move it to the bottom of the channel"
+                }
+                (true, false) => "change its name to show who edited it",
+                (false, _) => "move it to the bottom of the channel",
+            });
+        }
+
+        if replayed
+            .target
+            .as_ref()
+            .is_some_and(|target| target.mentions_neutralised)
+        {
+            response.push_str(
+                "\n\nsome mentions in the edit won't ping anyone, either because you can't ping \
+                 them yourself or because new pings are turned off in this server",
+            );
+        }
+
+        if undoable {
+            write!(
+                response,
+                "\n\nif you made a mistake, you can undo it for {} minutes by selecting `apps` \
+                 then `undo edit` on the message",
+                config.undo_window
+            )?;
+        }
+
+        response.push_str(&replayed.report());
+
+        Ok(response.chars().take(2000).collect())
+    }
+
+    pub async fn replay(&self, replay: &Replay<'_>) -> Result<Replayed, anyhow::Error> {
+        let _lock = ChannelLock::new(&self.replaying_channels, replay.channel_id)?;
+
+        let (webhook, thread_id) = self.webhook(replay.channel_id, replay.guild_id).await?;
+
+        let upload_limit = upload_limit(
+            self.cache
+                .guild(replay.guild_id)
+                .context("guild is not cached")?
+                .premium_tier(),
+        );
 
+        let max_replay_depth = self
+            .storage
+            .guild_config(replay.guild_id)
+            .await?
+            .max_replay_depth;
+        let (mut messages, skipped_weird) = self
+            .replay_messages(
+                replay.channel_id,
+                replay.target_id,
+                replay.out_of_order,
+                max_replay_depth,
+            )
+            .await?;
+        if !messages
+            .iter()
+            .any(|(message, ..)| message.id() == replay.target_id)
+        {
+            return Err(super::Error::Edit(Error::MessageWeird).into());
+        }
+        let out_of_order_header = if replay.out_of_order {
+            Some(self.out_of_order_header(replay, &messages).await?)
+        } else {
+            None
+        };
+
+        let mut replayed = Replayed {
+            skipped_weird,
+            left_behind: 0,
+            reactions_report: Vec::new(),
+            target: None,
+        };
+        let mut reposted_ids = HashMap::new();
+        let mut display_names = HashMap::new();
+        let mut created = Vec::new();
+        let reposting = async {
+            let mut index = 0;
+            while let Some((message, author_id, origin_id)) = messages.get(index) {
+                let member = self
+                    .cache
+                    .member(replay.guild_id, *author_id)
+                    .context("member is not cached")?;
+                let user = self
+                    .cache
+                    .user(*author_id)
+                    .context("message author user is not cached")?;
+                let author_name = self
+                    .member_display_name(replay.guild_id, *author_id, &mut display_names)
+                    .await
+                    .context("message author user is not cached")?;
+
+                let (attachments, attachment_urls) = self
+                    .attachments(message.attachments(), upload_limit)
+                    .await?;
+
+                let reply_header = join_headers(
+                    out_of_order_header.as_deref(),
+                    self.reply_header(
+                        message,
+                        replay.guild_id,
+                        &reposted_ids,
+                        &mut display_names,
+                        (replay.target_id, replay.target_content),
+                    )
+                    .await,
+                );
+                let message_content = with_reply_header(
+                    reply_header.as_deref(),
+                    format!("{}{attachment_urls}", message.content()),
+                );
+                let embeds = user_embeds(message.embeds());
+
+                let author_avatar_url = avatar_url(&member, &user);
+                let minimal_member = MinimalMember::new(&author_name, None, None);
+                let is_target = message.id() == replay.target_id;
+                let allowed_mentions = if is_target {
+                    target_mentions(
+                        message.mention_everyone(),
+                        message.mentions().iter().copied(),
+                        message.mention_roles().iter().copied(),
+                        replay.new_mentions,
+                    )
+                } else {
+                    AllowedMentions::default()
+                };
+                let minimal_webhook = MinimalWebhook::try_from(webhook.value())?;
+                let exec = minimal_webhook
+                    .execute_as_member(&self.http, thread_id, &minimal_member)?
+                    .avatar_url(&author_avatar_url)
+                    .allowed_mentions(Some(&allowed_mentions))
+                    .content(&message_content)?
+                    .embeds(&embeds)?
+                    .attachments(&attachments)?;
+                let new_message = if is_target {
+                    let target_content = with_reply_header(
+                        reply_header.as_deref(),
+                        format!("{}{attachment_urls}", replay.target_content),
+                    );
+                    let target_exec = exec.content(&target_content)?;
+                    if let Some(suffix) = replay.edited_suffix {
+                        target_exec
+                            .username(&edited_username(&author_name, suffix, replay.reason))?
+                            .wait()
+                            .exec()
+                            .await?
+                    } else {
+                        target_exec.wait().exec().await?
+                    }
+                } else {
+                    exec.wait().exec().await?
+                }
+                .model()
+                .await?;
+                created.push(new_message.id);
+                reposted_ids.insert(message.id(), new_message.id);
+                self.storage
+                    .set_reposted_message(
+                        new_message.id,
+                        &RepostedMessage {
+                            author_id: *author_id,
+                            origin_id: *origin_id,
+                            original_content: (is_target && replay.undoable)
+                                .then(|| message_content.clone()),
+                        },
+                    )
+                    .await?;
+                if is_target {
+                    replayed.target = Some(ReplayedTarget {
+                        message_id: new_message.id,
+                        author_id: *author_id,
+                        origin_id: *origin_id,
+                        original_content: message.content().to_owned(),
+                        original_timestamp: message.timestamp().as_secs(),
+                        timestamp: new_message.timestamp.as_secs(),
+                        mentions_neutralised: mentions_neutralised(
+                            replay.target_content,
+                            &allowed_mentions,
+                        ),
+                    });
+                }
+
+                if let Some(reaction_counts) = self.add_reactions(message, new_message.id).await {
+                    replayed.reactions_report.push(format!(
+                        "\nhttps://discord.com/channels/{}/{}/{}:{reaction_counts}",
+                        replay.guild_id, replay.channel_id, new_message.id
+                    ));
+                }
+
+                index += 1;
+                if index == messages.len() && !replay.out_of_order {
+                    let sent = self
+                        .sent_during_replay(
+                            replay.channel_id,
+                            (&messages, &created),
+                            &mut replayed.skipped_weird,
+                        )
+                        .await?;
+                    let room = usize::try_from(max_replay_depth)?.saturating_sub(messages.len());
+                    replayed.left_behind += sent.len().saturating_sub(room);
+                    messages.extend(sent.into_iter().take(room));
+                }
+            }
+
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+        if let Err(err) = reposting {
+            println!("{err:#?}");
+            return Err(self.roll_back(replay, &created).await.into());
+        }
+
+        if let Err(err) = self
+            .delete_replayed(
+                replay.channel_id,
+                &messages.iter().map(|(m, ..)| m.id()).collect::<Vec<_>>(),
+                replay.audit_reason,
+            )
+            .await
+        {
+            println!("{err:#?}");
+            return Err(super::Error::Edit(Error::DeleteFailed).into());
+        }
+
+        Ok(replayed)
+    }
+
+    async fn roll_back(&self, replay: &Replay<'_>, created: &[Id<MessageMarker>]) -> super::Error {
+        if created.is_empty() {
+            return super::Error::Edit(Error::RepostFailed);
+        }
+
+        match self
+            .delete_replayed(
+                replay.channel_id,
+                created,
+                "rolling back an edit that failed",
+            )
+            .await
+        {
+            Ok(()) => super::Error::Edit(Error::RolledBack(created.len())),
+            Err(err) => {
+                println!("{err:#?}");
+                let mut message_links = String::new();
+                #[allow(unused_must_use)]
+                for message_id in created {
+                    write!(
+                        message_links,
+                        "\nhttps://discord.com/channels/{}/{}/{message_id}",
+                        replay.guild_id, replay.channel_id
+                    );
+                }
+                super::Error::Edit(Error::RollbackFailed(message_links))
+            }
+        }
+    }
+
+    async fn replay_messages(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        target_id: Id<MessageMarker>,
+        out_of_order: bool,
+        max_replay_depth: u32,
+    ) -> Result<(Vec<ReplayMessage>, bool), anyhow::Error> {
+        let unfiltered = if out_of_order {
+            vec![self.message(channel_id, target_id).await?]
+        } else {
+            self.messages(channel_id, target_id, max_replay_depth)
+                .await?
+        };
+        if unfiltered.len() > usize::try_from(max_replay_depth)? {
+            return Err(super::Error::Edit(Error::TooManyMessages(max_replay_depth)).into());
+        }
+
+        let mut messages = Vec::new();
+        let mut skipped_weird = false;
+        for message in unfiltered {
+            let lineage = match message.webhook_id() {
+                None => Some((message.author(), message.id())),
+                Some(_) => self
+                    .storage
+                    .reposted_message(message.id())
+                    .await?
+                    .map(|reposted| (reposted.author_id, reposted.origin_id)),
+            };
+            match lineage {
+                Some((author_id, origin_id)) if !cached_message_is_weird(&message) => {
+                    messages.push((message, author_id, origin_id));
+                }
+                _ => skipped_weird = true,
+            }
+        }
+
+        Ok((messages, skipped_weird))
+    }
+
+    async fn sent_during_replay(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        (replaying, created): (&[ReplayMessage], &[Id<MessageMarker>]),
+        skipped_weird: &mut bool,
+    ) -> Result<Vec<ReplayMessage>, anyhow::Error> {
+        let sent_ids = self
+            .replaying_channels
+            .get_mut(&channel_id)
+            .map(|mut sent_ids| mem::take(&mut *sent_ids))
+            .unwrap_or_default();
+
+        let mut messages = Vec::new();
+        for message_id in sent_ids {
+            if created.contains(&message_id)
+                || replaying.iter().any(|(message, _, origin_id)| {
+                    message.id() == message_id || *origin_id == message_id
+                })
+            {
+                continue;
+            }
+            let message = match self.message(channel_id, message_id).await {
+                Ok(message) => message,
+                Err(_) => continue,
+            };
+            if message.webhook_id().is_some() || cached_message_is_weird(&message) {
+                *skipped_weird = true;
+            } else {
+                let author_id = message.author();
+                messages.push((message, author_id, message_id));
+            }
+        }
+
+        Ok(messages)
+    }
+
+    async fn delete_replayed(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        message_ids: &[Id<MessageMarker>],
+        audit_reason: &str,
+    ) -> Result<(), anyhow::Error> {
+        let (bulk_deletable, too_old) = partition_bulk_deletable(message_ids, unix_timestamp()?);
+
+        for chunk in bulk_deletable.chunks(100) {
+            if let [message_id] = chunk {
+                self.http
+                    .delete_message(channel_id, *message_id)
+                    .reason(audit_reason)?
+                    .exec()
+                    .await?;
+            } else {
+                self.http
+                    .delete_messages(channel_id, chunk)
+                    .reason(audit_reason)?
+                    .exec()
+                    .await?;
+            }
+        }
+        for message_id in too_old {
+            self.http
+                .delete_message(channel_id, message_id)
+                .reason(audit_reason)?
+                .exec()
+                .await?;
+        }
+
+        self.storage.remove_reposted_messages(message_ids).await?;
+
+        Ok(())
+    }
+
+    pub async fn check_author(
+        &self,
+        guild_id: Id<GuildMarker>,
+        editor_id: Id<UserMarker>,
+        author_id: Id<UserMarker>,
+    ) -> Result<(), anyhow::Error> {
+        if editor_id == author_id {
+            return Ok(());
+        }
+
+        if self.storage.opted_out(author_id, guild_id).await? {
+            return Err(super::Error::Edit(Error::AuthorOptedOut).into());
+        }
+
+        let owner_id = self
+            .cache
+            .guild(guild_id)
+            .context("guild is not cached")?
+            .owner_id();
+        if author_id == owner_id {
+            return Err(super::Error::Edit(Error::AuthorOwner).into());
+        }
+        if editor_id == owner_id {
+            return Ok(());
+        }
+
+        let author_role_ids = self.member_role_ids(guild_id, author_id);
+        let protected_ids = self.storage.protected_ids(guild_id).await?;
+        if protected_ids.contains(&author_id.cast())
+            || author_role_ids
+                .iter()
+                .any(|role_id| protected_ids.contains(&role_id.cast()))
+        {
+            return Err(super::Error::Edit(Error::AuthorProtected).into());
+        }
+
+        if self.highest_role_position(&self.member_role_ids(guild_id, editor_id))
+            <= self.highest_role_position(&author_role_ids)
+        {
+            return Err(super::Error::Edit(Error::AuthorHigherRole).into());
+        }
+
+        Ok(())
+    }
+
+    fn member_role_ids(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+    ) -> Vec<Id<RoleMarker>> {
+        self.cache
+            .member(guild_id, user_id)
+            .map(|member| member.roles().to_vec())
+            .unwrap_or_default()
+    }
+
+    fn highest_role_position(&self, role_ids: &[Id<RoleMarker>]) -> i64 {
+        role_ids
+            .iter()
+            .filter_map(|role_id| {
+                self.cache
+                    .role(*role_id)
+                    .map(|role| role.resource().position)
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    async fn new_mentions(
+        &self,
+        content: &str,
+        editor_id: Id<UserMarker>,
+        channel_id: Id<ChannelMarker>,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<AllowedMentions, anyhow::Error> {
+        if !self.storage.guild_config(guild_id).await?.new_mentions {
+            return Ok(AllowedMentions::default());
+        }
+
+        Ok(editor_mentions(
+            self.cache.permissions().in_channel(editor_id, channel_id)?,
+            content,
+            |role_id| {
+                self.cache
+                    .role(role_id)
+                    .is_some_and(|role| role.guild_id() == guild_id && role.resource().mentionable)
+            },
+        ))
+    }
+
+    #[allow(clippy::wildcard_enum_match_arm)]
+    pub fn check_channel(&self, channel_id: Id<ChannelMarker>) -> Result<(), anyhow::Error> {
+        let kind = self
+            .cache
+            .channel(channel_id)
+            .context("channel not cached")?
+            .kind;
+
+        match kind {
+            ChannelType::GuildText
+            | ChannelType::GuildNews
+            | ChannelType::GuildNewsThread
+            | ChannelType::GuildPublicThread
+            | ChannelType::GuildPrivateThread => Ok(()),
+            ChannelType::GuildVoice => Err(super::Error::Edit(Error::VoiceChannel).into()),
+            ChannelType::GuildStageVoice => Err(super::Error::Edit(Error::StageChannel).into()),
+            _ => Err(super::Error::Edit(Error::UnsupportedChannel).into()),
+        }
+    }
+
+    async fn message(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+    ) -> Result<CachedMessage, anyhow::Error> {
+        if let Some(message) = self.cache.message(message_id) {
+            return Ok(message.value().clone());
+        }
+
+        Ok(CachedMessage::from(
+            self.http
+                .message(channel_id, message_id)
+                .exec()
+                .await?
+                .model()
+                .await?,
+        ))
+    }
+
+    async fn out_of_order_header(
+        &self,
+        replay: &Replay<'_>,
+        messages: &[ReplayMessage],
+    ) -> Result<String, anyhow::Error> {
+        let timestamp = messages
+            .first()
+            .context("list of messages is empty")?
+            .0
+            .timestamp()
+            .as_secs();
+        let previous_message = self
+            .http
+            .channel_messages(replay.channel_id)
+            .before(replay.target_id)
+            .limit(1)?
+            .exec()
+            .await?
+            .models()
+            .await?;
+
+        Ok(previous_message.first().map_or_else(
+            || format!("> originally sent <t:{timestamp}:f>\n"),
+            |message| {
+                format!(
+                    "> originally sent <t:{timestamp}:f>, [jump to \
+                     context](https://discord.com/channels/{}/{}/{})\n",
+                    replay.guild_id, replay.channel_id, message.id
+                )
+            },
+        ))
+    }
+
+    async fn messages(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        target_id: Id<MessageMarker>,
+        max_replay_depth: u32,
+    ) -> Result<Vec<CachedMessage>, anyhow::Error> {
+        let cached_ids: Vec<_> = self
+            .cache
+            .channel_messages(channel_id)
+            .map(Iterator::collect)
+            .unwrap_or_default();
+        if let Some(target_index) = cached_ids.iter().position(|&id| id == target_id) {
+            if let Some(messages) = cached_ids
+                .get(..=target_index)
+                .unwrap_or_default()
+                .iter()
+                .rev()
+                .map(|&id| {
+                    self.cache
+                        .message(id)
+                        .map(|message| message.value().clone())
+                })
+                .collect()
+            {
+                return Ok(messages);
+            }
+        }
+
+        let mut messages = vec![self.message(channel_id, target_id).await?];
+        let mut after = target_id;
+        loop {
+            let mut page = self
+                .http
+                .channel_messages(channel_id)
+                .after(after)
+                .limit(100)?
+                .exec()
+                .await?
+                .models()
+                .await?;
+            page.sort_unstable_by_key(|message| message.id);
+            let is_last_page = page.len() < 100
+                || messages.len() + page.len() > usize::try_from(max_replay_depth)?;
+            if let Some(last) = page.last() {
+                after = last.id;
+            }
+            messages.extend(page.into_iter().map(CachedMessage::from));
+            if is_last_page {
+                break;
+            }
+        }
+
+        Ok(messages)
+    }
+
+    async fn webhook(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<
+        (
+            Ref<'_, Id<ChannelMarker>, Webhook>,
+            Option<Id<ChannelMarker>>,
+        ),
+        anyhow::Error,
+    > {
         let channel = self
             .cache
-            .channel(modal.channel_id)
+            .channel(channel_id)
             .context("channel not cached")?;
-        let (channel_id, thread_id) = if channel.kind.is_thread() {
+        let (webhook_channel_id, thread_id) = if channel.kind.is_thread() {
             (
                 channel
                     .parent_id
@@ -151,115 +1247,326 @@ impl<'ctx> Handler<'ctx> {
         } else {
             (channel.id, None)
         };
-        let input = modal
-            .data
-            .components
-            .pop()
-            .context("modal doesn't have any components")?
-            .components
-            .pop()
-            .context("modal action row doesn't have any components")?;
+
         let webhook = self
             .webhooks_cache
-            .get_infallible(&self.http, channel_id, "any message editor")
+            .get_infallible(
+                &self.http,
+                webhook_channel_id,
+                &self.storage.guild_config(guild_id).await?.webhook_name,
+            )
+            .await?;
+
+        Ok((webhook, thread_id))
+    }
+
+    pub async fn edit_in_place(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        guild_id: Id<GuildMarker>,
+        (message_id, reposted): (Id<MessageMarker>, RepostedMessage),
+        (content, new_mentions): (&str, &AllowedMentions),
+        undoable: bool,
+    ) -> Result<Option<ReplayedTarget>, anyhow::Error> {
+        let _lock = ChannelLock::new(&self.replaying_channels, channel_id)?;
+        let (webhook, thread_id) = self.webhook(channel_id, guild_id).await?;
+        let message = self
+            .http
+            .message(channel_id, message_id)
+            .exec()
+            .await?
+            .model()
             .await?;
-        let edit_message_id: Id<MessageMarker> = input.custom_id.parse()?;
+        if message.webhook_id != Some(webhook.id) {
+            return Ok(None);
+        }
+        let allowed_mentions = target_mentions(
+            message.mention_everyone,
+            message.mentions.iter().map(|mention| mention.id),
+            message.mention_roles.iter().copied(),
+            new_mentions,
+        );
+
+        let mut update = self
+            .http
+            .update_webhook_message(
+                webhook.id,
+                webhook
+                    .token
+                    .as_ref()
+                    .context("webhook doesn't have a token")?,
+                message_id,
+            )
+            .content(Some(content))?
+            .allowed_mentions(Some(&allowed_mentions));
+        if let Some(id) = thread_id {
+            update = update.thread_id(id);
+        }
+        update.exec().await?;
+
+        self.storage
+            .set_reposted_message(
+                message_id,
+                &RepostedMessage {
+                    original_content: undoable.then(|| message.content.clone()),
+                    ..reposted
+                },
+            )
+            .await?;
+
+        Ok(Some(ReplayedTarget {
+            message_id,
+            author_id: reposted.author_id,
+            origin_id: reposted.origin_id,
+            original_content: message.content,
+            original_timestamp: message
+                .edited_timestamp
+                .unwrap_or(message.timestamp)
+                .as_secs(),
+            timestamp: unix_timestamp()?,
+            mentions_neutralised: mentions_neutralised(content, &allowed_mentions),
+        }))
+    }
+
+    pub async fn add_version(
+        &self,
+        target: &ReplayedTarget,
+        editor_id: Id<UserMarker>,
+        content: &str,
+        reason: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        if self
+            .storage
+            .message_versions(target.origin_id)
+            .await?
+            .is_empty()
+        {
+            self.storage
+                .add_message_version(
+                    target.origin_id,
+                    &MessageVersion {
+                        author_id: target.author_id,
+                        editor_id: None,
+                        content: target.original_content.clone(),
+                        reason: None,
+                        timestamp: target.original_timestamp,
+                    },
+                )
+                .await?;
+        }
 
-        let mut reply = "done!";
-        let unfiltered = self
+        self.storage
+            .add_message_version(
+                target.origin_id,
+                &MessageVersion {
+                    author_id: target.author_id,
+                    editor_id: Some(editor_id),
+                    content: content.to_owned(),
+                    reason: reason.map(ToOwned::to_owned),
+                    timestamp: target.timestamp,
+                },
+            )
+            .await
+    }
+
+    async fn reply_header(
+        &self,
+        message: &CachedMessage,
+        guild_id: Id<GuildMarker>,
+        reposted_ids: &HashMap<Id<MessageMarker>, Id<MessageMarker>>,
+        display_names: &mut HashMap<Id<UserMarker>, String>,
+        (edit_message_id, edit_content): (Id<MessageMarker>, &str),
+    ) -> Option<String> {
+        let parent_id = message.reference()?.message_id?;
+        let jump_link = format!(
+            "https://discord.com/channels/{guild_id}/{}/{}",
+            message.channel_id(),
+            reposted_ids.get(&parent_id).unwrap_or(&parent_id)
+        );
+
+        let parent = self
             .cache
-            .channel_messages(modal.channel_id)
-            .context("channel messages aren't cached")?
-            .take_while(|&id| id != edit_message_id)
-            .chain([edit_message_id].into_iter())
-            .map(|id| self.cache.message(id).context("message is not cached"))
-            .collect::<Result<Vec<Reference<_, _>>, _>>()?;
-        let messages: Vec<_> = unfiltered
-            .iter()
-            .rev()
-            .filter(|m| {
-                if cached_message_is_weird(m) {
-                    reply = "done! there was a weird message sent after the message to edit so i \
-                             left it alone";
-                    false
+            .message(parent_id)
+            .map(|parent| (parent.author(), parent.content().to_owned()));
+        match parent {
+            Some((author_id, parent_content)) => {
+                let author_name = self
+                    .member_display_name(guild_id, author_id, display_names)
+                    .await
+                    .unwrap_or_else(|| "someone".to_owned());
+                let content = if parent_id == edit_message_id {
+                    edit_content
                 } else {
-                    true
-                }
-            })
-            .collect();
-        for message in &messages {
-            let author_id = message.author();
-            let member = self
-                .cache
-                .member(
-                    message
-                        .guild_id()
-                        .context("message doesn't have a guild id")?,
-                    author_id,
-                )
-                .context("member is not cached")?;
-            let user = self
-                .cache
-                .user(author_id)
-                .context("message author user is not cached")?;
-
-            let mut content = message.content().to_owned();
-            #[allow(unused_must_use)]
-            for attachment in message.attachments() {
-                write!(content, "\n{}", attachment.url);
+                    &parent_content
+                };
+
+                Some(format!(
+                    "> replying to @{author_name}: {} ([jump]({jump_link}))\n",
+                    snippet(content)
+                ))
             }
+            None => Some(format!("> replying to [a message]({jump_link})\n")),
+        }
+    }
 
-            let minimal_member = MinimalMember::from_cached_member(&member, &user);
-            let minimal_webhook = MinimalWebhook::try_from(webhook.value())?;
-            let exec = minimal_webhook
-                .execute_as_member(&self.http, thread_id, &minimal_member)?
-                .content(&content)?;
-            if message.id() == edit_message_id {
-                let interaction_member = modal
-                    .member
-                    .as_ref()
-                    .context("modal interaction doesn't have a member")?;
-                exec.content(&input.value)?
-                    .username(&format!(
-                        "{} (edited by {})",
-                        member.nick().unwrap_or(&user.name),
-                        interaction_member.nick.as_ref().unwrap_or(
-                            &interaction_member
-                                .user
-                                .as_ref()
-                                .context("modal interaction member doesn't include user info")?
-                                .name
-                        )
-                    ))?
-                    .wait()
-                    .exec()
-                    .await?;
-            } else {
-                exec.wait().exec().await?;
-            };
+    pub async fn display_name(&self, nick: Option<&str>, user: &User) -> String {
+        match nick {
+            Some(name) => name.to_owned(),
+            None => self
+                .global_name(user.id)
+                .await
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| user.name.clone()),
         }
+    }
 
-        if messages.len() == 1 {
-            self.http
-                .delete_message(
-                    modal.channel_id,
-                    messages.first().context("list of messages is empty")?.id(),
-                )
-                .exec()
-        } else {
-            self.http
-                .delete_messages(
-                    modal.channel_id,
-                    &messages.iter().map(|m| m.id()).collect::<Vec<_>>(),
+    async fn member_display_name(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        display_names: &mut HashMap<Id<UserMarker>, String>,
+    ) -> Option<String> {
+        if let Some(name) = display_names.get(&user_id) {
+            return Some(name.clone());
+        }
+
+        let user = self.cache.user(user_id)?.value().clone();
+        let nick = self
+            .cache
+            .member(guild_id, user_id)
+            .and_then(|member| member.nick().map(ToOwned::to_owned));
+        let name = self.display_name(nick.as_deref(), &user).await;
+        display_names.insert(user_id, name.clone());
+
+        Some(name)
+    }
+
+    async fn global_name(&self, user_id: Id<UserMarker>) -> Result<Option<String>, anyhow::Error> {
+        let user: GlobalName =
+            serde_json::from_slice(&self.http.user(user_id).exec().await?.bytes().await?)?;
+
+        Ok(user.global_name)
+    }
+
+    async fn attachments(
+        &self,
+        attachments: &[Attachment],
+        upload_limit: u64,
+    ) -> Result<(Vec<HttpAttachment>, String), anyhow::Error> {
+        let mut files = Vec::new();
+        let mut urls = String::new();
+        let mut upload_size = 0;
+
+        #[allow(unused_must_use)]
+        for (id, attachment) in (0..).zip(attachments) {
+            if upload_size + attachment.size > upload_limit {
+                write!(urls, "\n{}", attachment.url);
+                continue;
+            }
+            upload_size += attachment.size;
+
+            let mut file = HttpAttachment::from_bytes(
+                attachment.filename.clone(),
+                self.download(&attachment.url).await?,
+                id,
+            );
+            if let Some(description) = &attachment.description {
+                file.description(description.clone());
+            }
+            files.push(file);
+        }
+
+        Ok((files, urls))
+    }
+
+    async fn add_reactions(
+        &self,
+        message: &CachedMessage,
+        new_message_id: Id<MessageMarker>,
+    ) -> Option<String> {
+        let mut reaction_counts = String::new();
+
+        #[allow(unused_must_use)]
+        for reaction in message.reactions() {
+            let added = self
+                .http
+                .create_reaction(
+                    message.channel_id(),
+                    new_message_id,
+                    &request_reaction_type(&reaction.emoji),
                 )
                 .exec()
+                .await
+                .is_ok();
+            write!(
+                reaction_counts,
+                " {} {}{}",
+                reaction_mention(&reaction.emoji),
+                reaction.count,
+                if added { "" } else { " (couldn't add it)" }
+            );
         }
-        .await?;
 
-        self.update_response().content(reply).exec().await?;
+        (!reaction_counts.is_empty()).then_some(reaction_counts)
+    }
+
+    async fn log_edit(
+        &self,
+        log_channel_id: Id<ChannelMarker>,
+        log: &EditLog<'_>,
+    ) -> Result<(), anyhow::Error> {
+        self.http
+            .create_message(log_channel_id)
+            .embeds(&[log.embed()?])?
+            .exec()
+            .await?;
 
         Ok(())
     }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>, anyhow::Error> {
+        Ok(self
+            .reqwest
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?
+            .to_vec())
+    }
+}
+
+impl Replayed {
+    #[allow(unused_must_use)]
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+
+        if self.skipped_weird {
+            report.push_str("\n\nthere was a weird message sent after this one so i left it alone");
+        }
+
+        if self.left_behind > 0 {
+            write!(
+                report,
+                "\n\n{} messages were sent while i was re-posting but i couldn't re-post them \
+                 too because of the max replay depth, so they're mixed in with the re-posted \
+                 messages and the order is off",
+                self.left_behind
+            );
+        }
+
+        if !self.reactions_report.is_empty() {
+            report.push_str(
+                "\n\ni added the reactions back but their counts started over, these were the \
+                 original counts:",
+            );
+            report.push_str(&self.reactions_report.concat());
+        }
+
+        report
+    }
 }
 
 pub fn build() -> Command {
@@ -268,31 +1575,503 @@ pub fn build() -> Command {
         .build()
 }
 
+#[allow(clippy::wildcard_enum_match_arm)]
+const fn upload_limit(premium_tier: PremiumTier) -> u64 {
+    match premium_tier {
+        PremiumTier::Tier2 => 50 * 1024 * 1024,
+        PremiumTier::Tier3 => 100 * 1024 * 1024,
+        _ => 8 * 1024 * 1024,
+    }
+}
+
+impl<'a> ChannelLock<'a> {
+    fn new(
+        replaying_channels: &'a DashMap<Id<ChannelMarker>, Vec<Id<MessageMarker>>>,
+        channel_id: Id<ChannelMarker>,
+    ) -> Result<Self, anyhow::Error> {
+        match replaying_channels.entry(channel_id) {
+            Entry::Occupied(_) => Err(super::Error::Edit(Error::ChannelBusy).into()),
+            Entry::Vacant(entry) => {
+                entry.insert(Vec::new());
+                Ok(Self {
+                    replaying_channels,
+                    channel_id,
+                })
+            }
+        }
+    }
+}
+
+impl Drop for ChannelLock<'_> {
+    fn drop(&mut self) {
+        self.replaying_channels.remove(&self.channel_id);
+    }
+}
+
+#[allow(unused_must_use)]
+impl EditLog<'_> {
+    fn embed(&self) -> Result<Embed, anyhow::Error> {
+        let mut diff = String::new();
+        for change in
+            TextDiff::from_lines(self.original_content, self.edited_content).iter_all_changes()
+        {
+            let sign = match change.tag() {
+                ChangeTag::Delete => '-',
+                ChangeTag::Insert => '+',
+                ChangeTag::Equal => ' ',
+            };
+            write!(diff, "{sign} {change}");
+            if change.missing_newline() {
+                diff.push('\n');
+            }
+        }
+
+        Ok(EmbedBuilder::new()
+            .title("message edited")
+            .url(format!(
+                "https://discord.com/channels/{}/{}/{}",
+                self.guild_id, self.channel_id, self.message_id
+            ))
+            .description(format!(
+                "```diff\n{}```",
+                truncate(&diff.replace('`', "`\u{200b}"), 3000)
+            ))
+            .field(EmbedFieldBuilder::new("author", format!("<@{}>", self.author_id)).inline())
+            .field(EmbedFieldBuilder::new("editor", format!("<@{}>", self.editor_id)).inline())
+            .field(EmbedFieldBuilder::new("reason", self.reason.unwrap_or("*none*")).inline())
+            .field(EmbedFieldBuilder::new(
+                "original content",
+                truncate(self.original_content, 1024),
+            ))
+            .field(EmbedFieldBuilder::new(
+                "new content",
+                truncate(self.edited_content, 1024),
+            ))
+            .validate()?
+            .build())
+    }
+}
+
+fn partition_bulk_deletable(
+    message_ids: &[Id<MessageMarker>],
+    now: i64,
+) -> (Vec<Id<MessageMarker>>, Vec<Id<MessageMarker>>) {
+    let bulk_delete_cutoff = (now - BULK_DELETE_MAX_AGE) * 1000;
+
+    message_ids
+        .iter()
+        .partition(|message_id| message_id.timestamp() > bulk_delete_cutoff)
+}
+
+fn confirmation_button(
+    (name, label): (&str, &str),
+    pending_edit_id: Id<InteractionMarker>,
+    style: ButtonStyle,
+) -> Component {
+    Component::Button(Button {
+        custom_id: Some(format!("{name}:{pending_edit_id}")),
+        disabled: false,
+        emoji: None,
+        label: Some(label.to_owned()),
+        style,
+        url: None,
+    })
+}
+
+fn pending_edit_id(custom_id: &str) -> Result<Id<InteractionMarker>, anyhow::Error> {
+    Ok(custom_id
+        .split_once(':')
+        .context("component custom id doesn't have the pending edit id")?
+        .1
+        .parse()?)
+}
+
+fn answered_no(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "no" | "n")
+}
+
+pub fn unix_timestamp() -> Result<i64, anyhow::Error> {
+    Ok(i64::try_from(
+        SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    )?)
+}
+
+pub fn truncate(content: &str, max_chars: usize) -> String {
+    if content.is_empty() {
+        "*empty*".to_owned()
+    } else if content.chars().count() > max_chars {
+        let mut truncated: String = content.chars().take(max_chars - 3).collect();
+        truncated.push_str("...");
+        truncated
+    } else {
+        content.to_owned()
+    }
+}
+
+fn avatar_url(member: &CachedMember, user: &User) -> String {
+    member.avatar().map_or_else(
+        || {
+            user.avatar.map_or_else(
+                || {
+                    let index = if user.discriminator == 0 {
+                        user.id.get().wrapping_shr(22).rem_euclid(6)
+                    } else {
+                        u64::from(user.discriminator.rem_euclid(5))
+                    };
+                    format!("https://cdn.discordapp.com/embed/avatars/{index}.png")
+                },
+                |hash| format!("https://cdn.discordapp.com/avatars/{}/{hash}.png", user.id),
+            )
+        },
+        |hash| {
+            format!(
+                "https://cdn.discordapp.com/guilds/{}/users/{}/avatars/{hash}.png",
+                member.guild_id(),
+                user.id
+            )
+        },
+    )
+}
+
+fn editor_mentions(
+    permissions: Permissions,
+    content: &str,
+    is_mentionable: impl Fn(Id<RoleMarker>) -> bool,
+) -> AllowedMentions {
+    if permissions.contains(Permissions::MENTION_EVERYONE) {
+        return AllowedMentions::builder()
+            .everyone()
+            .users()
+            .roles()
+            .build();
+    }
+
+    AllowedMentions::builder()
+        .users()
+        .role_ids(
+            mention_ids(content, "<@&")
+                .into_iter()
+                .filter(|role_id| is_mentionable(*role_id)),
+        )
+        .build()
+}
+
+fn target_mentions(
+    everyone: bool,
+    user_ids: impl IntoIterator<Item = Id<UserMarker>>,
+    role_ids: impl IntoIterator<Item = Id<RoleMarker>>,
+    new_mentions: &AllowedMentions,
+) -> AllowedMentions {
+    let mut allowed = AllowedMentions {
+        parse: new_mentions.parse.clone(),
+        ..AllowedMentions::default()
+    };
+
+    if everyone && !allowed.parse.contains(&ParseTypes::Everyone) {
+        allowed.parse.push(ParseTypes::Everyone);
+    }
+    if !allowed.parse.contains(&ParseTypes::Users) {
+        allowed.users.extend(user_ids);
+        allowed.users.extend(&new_mentions.users);
+    }
+    if !allowed.parse.contains(&ParseTypes::Roles) {
+        allowed.roles.extend(role_ids);
+        allowed.roles.extend(&new_mentions.roles);
+        allowed.roles.sort_unstable();
+        allowed.roles.dedup();
+        allowed.roles.truncate(100);
+    }
+
+    allowed
+}
+
+fn mention_ids<T>(content: &str, prefix: &str) -> Vec<Id<T>> {
+    content
+        .split(prefix)
+        .skip(1)
+        .filter_map(|mention| {
+            let (id, _) = mention.split_once('>')?;
+            id.trim_start_matches('!').parse().ok()
+        })
+        .collect()
+}
+
+fn mentions_neutralised(content: &str, allowed: &AllowedMentions) -> bool {
+    ((content.contains("@everyone") || content.contains("@here"))
+        && !allowed.parse.contains(&ParseTypes::Everyone))
+        || (!allowed.parse.contains(&ParseTypes::Users)
+            && mention_ids(content, "<@")
+                .iter()
+                .any(|id| !allowed.users.contains(id)))
+        || (!allowed.parse.contains(&ParseTypes::Roles)
+            && mention_ids(content, "<@&")
+                .iter()
+                .any(|id| !allowed.roles.contains(id)))
+}
+
+fn edited_username(name: &str, suffix: &str, reason: Option<&str>) -> String {
+    let mut username = String::new();
+    push_truncated(&mut username, name, NAME_MAX_CHARS);
+    username.push_str(" (");
+    let suffix_room = USERNAME_MAX_CHARS.saturating_sub(username.chars().count() + ")".len());
+    push_truncated(&mut username, suffix, suffix_room);
+
+    if let Some(edit_reason) = reason {
+        let room = USERNAME_MAX_CHARS.saturating_sub(username.chars().count() + ": )".len());
+        if room > 0 {
+            username.push_str(": ");
+            push_truncated(&mut username, edit_reason, room);
+        }
+    }
+
+    username.push(')');
+    username
+}
+
+fn push_truncated(target: &mut String, value: &str, max_chars: usize) {
+    if value.chars().count() <= max_chars {
+        target.push_str(value);
+    } else if max_chars > 3 {
+        target.extend(value.chars().take(max_chars - 3));
+        target.push_str("...");
+    } else {
+        target.extend(value.chars().take(max_chars));
+    }
+}
+
+fn join_headers(out_of_order_header: Option<&str>, reply_header: Option<String>) -> Option<String> {
+    match (out_of_order_header, reply_header) {
+        (Some(first), Some(second)) => Some(format!("{first}{second}")),
+        (Some(header), None) => Some(header.to_owned()),
+        (None, header) => header,
+    }
+}
+
+fn with_reply_header(reply_header: Option<&str>, content: String) -> String {
+    match reply_header {
+        Some(header) if header.chars().count() + content.chars().count() <= 2000 => {
+            format!("{header}{content}")
+        }
+        _ => content,
+    }
+}
+
+fn snippet(content: &str) -> String {
+    if content.is_empty() {
+        return "*click to see attachment*".to_owned();
+    }
+
+    let mut snippet: String = content
+        .chars()
+        .take(50)
+        .map(|c| if c == '\n' { ' ' } else { c })
+        .collect::<String>()
+        .replace('@', "@\u{200b}");
+    if content.chars().count() > 50 {
+        snippet.push_str("...");
+    }
+
+    snippet
+}
+
+fn request_reaction_type(emoji: &ReactionType) -> RequestReactionType<'_> {
+    match emoji {
+        ReactionType::Custom { id, name, .. } => RequestReactionType::Custom {
+            id: *id,
+            name: name.as_deref(),
+        },
+        ReactionType::Unicode { name } => RequestReactionType::Unicode { name },
+    }
+}
+
+fn reaction_mention(emoji: &ReactionType) -> String {
+    match emoji {
+        ReactionType::Custom { animated, id, name } => format!(
+            "<{}:{}:{id}>",
+            if *animated { "a" } else { "" },
+            name.as_deref().unwrap_or("_")
+        ),
+        ReactionType::Unicode { name } => name.clone(),
+    }
+}
+
+fn user_embeds(embeds: &[Embed]) -> Vec<Embed> {
+    embeds
+        .iter()
+        .filter(|embed| embed.kind == "rich")
+        .cloned()
+        .collect()
+}
+
 fn message_is_weird(message: &Message) -> bool {
     message.activity.is_some()
         || message.application.is_some()
         || message.application_id.is_some()
-        || message.author.bot
         || !message.components.is_empty()
-        || !message.embeds.is_empty()
         || message.interaction.is_some()
         || !matches!(message.kind, MessageType::Regular | MessageType::Reply)
         || message.pinned
-        || !message.reactions.is_empty()
         || !message.sticker_items.is_empty()
-        || message.webhook_id.is_some()
+}
+
+fn is_forum_post_starter(message_id: Id<MessageMarker>, channel_id: Id<ChannelMarker>) -> bool {
+    message_id.cast() == channel_id
 }
 
 fn cached_message_is_weird(message: &CachedMessage) -> bool {
-    message.activity().is_some()
+    is_forum_post_starter(message.id(), message.channel_id())
+        || message.activity().is_some()
         || message.application().is_some()
         || message.application_id().is_some()
         || !message.components().is_empty()
-        || !message.embeds().is_empty()
         || message.interaction().is_some()
         || !matches!(message.kind(), MessageType::Regular | MessageType::Reply)
         || message.pinned()
-        || !message.reactions().is_empty()
         || !message.sticker_items().is_empty()
-        || message.webhook_id().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::{
+        channel::message::{allowed_mentions::ParseTypes, AllowedMentions},
+        guild::Permissions,
+        id::{
+            marker::{MessageMarker, RoleMarker, UserMarker},
+            Id,
+        },
+    };
+
+    use super::{
+        edited_username, editor_mentions, mention_ids, mentions_neutralised,
+        partition_bulk_deletable, target_mentions, BULK_DELETE_MAX_AGE, EDITED_SUFFIX_MAX_CHARS,
+        NAME_MAX_CHARS, USERNAME_MAX_CHARS,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn message_id_sent_at(timestamp: i64) -> Id<MessageMarker> {
+        Id::new(
+            u64::try_from(timestamp * 1000 - 1_420_070_400_000)
+                .unwrap_or_default()
+                .wrapping_shl(22),
+        )
+    }
+
+    #[test]
+    fn everyone_without_permission() {
+        let new_mentions = editor_mentions(Permissions::SEND_MESSAGES, "@everyone", |_| true);
+        let allowed = target_mentions(false, [], [], &new_mentions);
+
+        assert!(!allowed.parse.contains(&ParseTypes::Everyone));
+        assert!(mentions_neutralised("look @everyone", &allowed));
+        assert!(mentions_neutralised("look @here", &allowed));
+    }
+
+    #[test]
+    fn everyone_with_permission() {
+        let new_mentions = editor_mentions(Permissions::MENTION_EVERYONE, "", |_| false);
+        let allowed = target_mentions(false, [], [], &new_mentions);
+
+        assert!(!mentions_neutralised("look @everyone <@&1> <@2>", &allowed));
+    }
+
+    #[test]
+    fn everyone_pinged_by_original() {
+        let allowed = target_mentions(true, [], [], &AllowedMentions::default());
+
+        assert!(!mentions_neutralised("look @everyone", &allowed));
+    }
+
+    #[test]
+    fn non_mentionable_roles() {
+        let content = "<@&1> <@&2>";
+        let new_mentions = editor_mentions(Permissions::empty(), content, |role_id| {
+            role_id == Id::new(1)
+        });
+        let allowed = target_mentions(false, [], [], &new_mentions);
+
+        assert_eq!(allowed.roles, [Id::new(1)]);
+        assert!(!mentions_neutralised("<@&1>", &allowed));
+        assert!(mentions_neutralised(content, &allowed));
+    }
+
+    #[test]
+    fn roles_pinged_by_original() {
+        let allowed = target_mentions(false, [], [Id::new(2)], &AllowedMentions::default());
+
+        assert!(!mentions_neutralised("<@&2>", &allowed));
+        assert!(mentions_neutralised("<@&3>", &allowed));
+    }
+
+    #[test]
+    fn user_mentions() {
+        let new_mentions = editor_mentions(Permissions::empty(), "<@5>", |_| false);
+        let allowed = target_mentions(false, [], [], &new_mentions);
+
+        assert!(!mentions_neutralised("<@5> <@!6>", &allowed));
+    }
+
+    #[test]
+    fn user_mentions_without_new_mentions() {
+        let allowed = target_mentions(false, [Id::new(5)], [], &AllowedMentions::default());
+
+        assert!(!mentions_neutralised("<@5> <@!5>", &allowed));
+        assert!(mentions_neutralised("<@!6>", &allowed));
+    }
+
+    #[test]
+    fn mention_ids_by_kind() {
+        let content = "<@1> <@!2> <@&3> <@nope> <@4";
+
+        assert_eq!(
+            mention_ids::<UserMarker>(content, "<@"),
+            [Id::new(1), Id::new(2)]
+        );
+        assert_eq!(mention_ids::<RoleMarker>(content, "<@&"), [Id::new(3)]);
+    }
+
+    #[test]
+    fn edited_username_fits() {
+        assert_eq!(
+            edited_username("lara", "edited by mod", Some("typo")),
+            "lara (edited by mod: typo)"
+        );
+        assert_eq!(
+            edited_username("lara", "edited by mod", None),
+            "lara (edited by mod)"
+        );
+    }
+
+    #[test]
+    fn edited_username_too_long() {
+        let username = edited_username(&"n".repeat(40), &"s".repeat(70), Some(&"r".repeat(100)));
+
+        assert!(username.chars().count() <= USERNAME_MAX_CHARS);
+        assert!(username.starts_with(&format!("{}... (", "n".repeat(NAME_MAX_CHARS - 3))));
+        assert!(username.ends_with("...)"));
+    }
+
+    #[test]
+    fn edited_username_longest_suffix() {
+        let name = "n".repeat(NAME_MAX_CHARS);
+        let suffix = "s".repeat(EDITED_SUFFIX_MAX_CHARS);
+        let username = edited_username(&name, &suffix, Some("reason"));
+
+        assert_eq!(username, format!("{name} ({suffix})"));
+        assert_eq!(username.chars().count(), USERNAME_MAX_CHARS);
+    }
+
+    #[test]
+    fn bulk_delete_cutoff() {
+        let recent = message_id_sent_at(NOW - 60);
+        let almost_too_old = message_id_sent_at(NOW - BULK_DELETE_MAX_AGE + 60);
+        let just_too_old = message_id_sent_at(NOW - BULK_DELETE_MAX_AGE - 60);
+        let way_too_old = message_id_sent_at(NOW - 30 * 24 * 60 * 60);
+
+        let (bulk_deletable, too_old) =
+            partition_bulk_deletable(&[recent, just_too_old, almost_too_old, way_too_old], NOW);
+
+        assert_eq!(bulk_deletable, [recent, almost_too_old]);
+        assert_eq!(too_old, [just_too_old, way_too_old]);
+    }
 }
@@ -0,0 +1,179 @@
+use std::ops::Deref;
+
+use anyhow::Context;
+use thiserror::Error;
+use twilight_model::{
+    application::{
+        command::{Command, CommandType},
+        component::{button::ButtonStyle, ActionRow, Button, Component},
+        interaction::{message_component::MessageComponentInteraction, ApplicationCommand},
+    },
+    guild::Permissions,
+    id::{marker::MessageMarker, Id},
+};
+use twilight_util::builder::command::CommandBuilder;
+
+use crate::{
+    interaction::{self, edit},
+    storage::MessageVersion,
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("this message wasn't edited with me so it doesn't have any history")]
+    NoHistory,
+}
+
+pub struct Handler<'ctx>(&'ctx interaction::Handler<'ctx>);
+
+impl<'ctx> Deref for Handler<'ctx> {
+    type Target = interaction::Handler<'ctx>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<'ctx> Handler<'ctx> {
+    pub const fn new(interaction_handler: &'ctx interaction::Handler) -> Self {
+        Self(interaction_handler)
+    }
+
+    pub async fn command(&self, command: ApplicationCommand) -> Result<(), anyhow::Error> {
+        self.defer().await?;
+
+        let guild_id = command.guild_id.context("command isn't run in a guild")?;
+        let user_id = command
+            .member
+            .as_ref()
+            .and_then(|member| member.user.as_ref())
+            .context("command member doesn't include user info")?
+            .id;
+        let editor_permission = self.storage.guild_config(guild_id).await?.editor_permission;
+        self.check_permissions(user_id, guild_id, editor_permission)?;
+        self.check_channel_permissions(user_id, command.channel_id, editor_permission)?;
+
+        let message_id = command
+            .data
+            .resolved
+            .context("command data doesn't have resolved data")?
+            .messages
+            .into_keys()
+            .next()
+            .context("command data doesn't have a message")?;
+        let origin_id = self
+            .storage
+            .reposted_message(message_id)
+            .await?
+            .map_or(message_id, |reposted| reposted.origin_id);
+
+        self.show_version(origin_id, 0).await
+    }
+
+    pub async fn component(
+        &self,
+        component: MessageComponentInteraction,
+    ) -> Result<(), anyhow::Error> {
+        self.defer_update().await?;
+
+        let mut custom_id = component.data.custom_id.split(':').skip(1);
+        let origin_id = custom_id
+            .next()
+            .context("component custom id doesn't have the origin id")?
+            .parse()?;
+        let page = custom_id
+            .next()
+            .context("component custom id doesn't have the page")?
+            .parse()?;
+
+        self.show_version(origin_id, page).await
+    }
+
+    async fn show_version(
+        &self,
+        origin_id: Id<MessageMarker>,
+        page: usize,
+    ) -> Result<(), anyhow::Error> {
+        let versions = self.storage.message_versions(origin_id).await?;
+        if versions.is_empty() {
+            return Err(super::Error::History(Error::NoHistory).into());
+        }
+        let last_page = versions.len() - 1;
+        let current_page = page.min(last_page);
+        let version = versions
+            .get(current_page)
+            .context("message version doesn't exist")?;
+
+        self.update_response()
+            .content(&version_content(version, current_page, versions.len()))
+            .components(&[Component::ActionRow(ActionRow {
+                components: vec![
+                    page_button(
+                        "previous",
+                        origin_id,
+                        current_page.saturating_sub(1),
+                        current_page == 0,
+                    ),
+                    page_button(
+                        "next",
+                        origin_id,
+                        current_page + 1,
+                        current_page == last_page,
+                    ),
+                ],
+            })])
+            .exec()
+            .await
+    }
+}
+
+pub fn build() -> Command {
+    CommandBuilder::new(
+        "view edit history".to_owned(),
+        "".to_owned(),
+        CommandType::Message,
+    )
+    .default_member_permissions(Permissions::MANAGE_MESSAGES)
+    .build()
+}
+
+fn version_content(version: &MessageVersion, page: usize, version_count: usize) -> String {
+    let details = version.editor_id.map_or_else(
+        || {
+            format!(
+                "**sent:** <t:{}:f>, this is the original",
+                version.timestamp
+            )
+        },
+        |editor_id| {
+            format!(
+                "**edited by:** <@{editor_id}> <t:{}:f>\n**reason:** {}",
+                version.timestamp,
+                version.reason.as_deref().unwrap_or("*none*")
+            )
+        },
+    );
+
+    format!(
+        "**version {} of {version_count}**\n**author:** <@{}>\n{details}\n\n{}",
+        page + 1,
+        version.author_id,
+        edit::truncate(&version.content, 1800)
+    )
+}
+
+fn page_button(
+    label: &str,
+    origin_id: Id<MessageMarker>,
+    page: usize,
+    disabled: bool,
+) -> Component {
+    Component::Button(Button {
+        custom_id: Some(format!("edit_history:{origin_id}:{page}")),
+        disabled,
+        emoji: None,
+        label: Some(label.to_owned()),
+        style: ButtonStyle::Secondary,
+        url: None,
+    })
+}
@@ -0,0 +1,70 @@
+use std::ops::Deref;
+
+use anyhow::Context;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::interaction::ApplicationCommand;
+
+use crate::interaction;
+
+#[derive(CreateCommand, CommandModel)]
+#[command(
+    name = "optout",
+    desc = "set whether others can edit your messages with me"
+)]
+pub struct ChatInput {
+    /// if on, nobody can edit your messages with me
+    opted_out: bool,
+    /// if on, this applies to every server instead of just this one
+    everywhere: Option<bool>,
+}
+
+pub struct Handler<'ctx>(&'ctx interaction::Handler<'ctx>);
+
+impl<'ctx> Deref for Handler<'ctx> {
+    type Target = interaction::Handler<'ctx>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<'ctx> Handler<'ctx> {
+    pub const fn new(interaction_handler: &'ctx interaction::Handler) -> Self {
+        Self(interaction_handler)
+    }
+
+    pub async fn command(&self, command: ApplicationCommand) -> Result<(), anyhow::Error> {
+        self.defer().await?;
+
+        let user_id = command
+            .member
+            .as_ref()
+            .and_then(|member| member.user.as_ref())
+            .or(command.user.as_ref())
+            .context("command doesn't include user info")?
+            .id;
+        let options = ChatInput::from_interaction(command.data.into())?;
+        let guild_id = if options.everywhere.unwrap_or_default() {
+            None
+        } else {
+            command.guild_id
+        };
+
+        self.storage
+            .set_opted_out(user_id, guild_id, options.opted_out)
+            .await?;
+
+        let scope = if guild_id.is_some() {
+            "in this server"
+        } else {
+            "in any server"
+        };
+        let reply = if options.opted_out {
+            format!("done! nobody can edit your messages with me {scope} now")
+        } else {
+            format!("done! your messages can be edited with me {scope} again")
+        };
+
+        self.update_response().content(&reply).exec().await
+    }
+}
@@ -0,0 +1,170 @@
+use std::ops::Deref;
+
+use anyhow::Context;
+use thiserror::Error;
+use twilight_model::{
+    application::{
+        command::{Command, CommandType},
+        interaction::ApplicationCommand,
+    },
+    channel::message::AllowedMentions,
+    guild::Permissions,
+};
+use twilight_util::builder::command::CommandBuilder;
+
+use crate::interaction::{self, edit};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("undoing edits is turned off in this server")]
+    Disabled,
+    #[error(
+        "i can only undo messages i edited, maybe someone edited or undid it again after that"
+    )]
+    NotEdited,
+    #[error("this edit is too old to undo, edits can only be undone for {0} minutes")]
+    Expired(u32),
+}
+
+pub struct Handler<'ctx>(&'ctx interaction::Handler<'ctx>);
+
+impl<'ctx> Deref for Handler<'ctx> {
+    type Target = interaction::Handler<'ctx>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<'ctx> Handler<'ctx> {
+    pub const fn new(interaction_handler: &'ctx interaction::Handler) -> Self {
+        Self(interaction_handler)
+    }
+
+    pub async fn command(&self, command: ApplicationCommand) -> Result<(), anyhow::Error> {
+        self.defer().await?;
+
+        self.edit().check_channel(command.channel_id)?;
+
+        self.check_self_permissions(
+            command.channel_id,
+            Permissions::MANAGE_MESSAGES
+                | Permissions::MANAGE_WEBHOOKS
+                | Permissions::READ_MESSAGE_HISTORY,
+        )?;
+
+        let guild_id = command.guild_id.context("command isn't run in a guild")?;
+        let config = self.storage.guild_config(guild_id).await?;
+        let interaction_member = command
+            .member
+            .as_ref()
+            .context("command doesn't have a member")?;
+        let undoer = interaction_member
+            .user
+            .as_ref()
+            .context("command member doesn't include user info")?;
+        self.check_permissions(undoer.id, guild_id, config.editor_permission)?;
+        self.check_channel_permissions(undoer.id, command.channel_id, config.editor_permission)?;
+
+        if config.undo_window == 0 {
+            return Err(super::Error::Undo(Error::Disabled).into());
+        }
+
+        let message = command
+            .data
+            .resolved
+            .as_ref()
+            .context("command data doesn't have resolved data")?
+            .messages
+            .values()
+            .next()
+            .context("command data doesn't have a message")?;
+
+        let reposted = self
+            .storage
+            .reposted_message(message.id)
+            .await?
+            .ok_or(super::Error::Undo(Error::NotEdited))?;
+        self.edit()
+            .check_author(guild_id, undoer.id, reposted.author_id)
+            .await?;
+        let original_content = reposted
+            .original_content
+            .clone()
+            .ok_or(super::Error::Undo(Error::NotEdited))?;
+
+        let elapsed = edit::unix_timestamp()?
+            - message
+                .edited_timestamp
+                .unwrap_or(message.timestamp)
+                .as_secs();
+        if elapsed > i64::from(config.undo_window) * 60 {
+            return Err(super::Error::Undo(Error::Expired(config.undo_window)).into());
+        }
+
+        let audit_reason = format!(
+            "edit undone by {}",
+            self.edit()
+                .display_name(interaction_member.nick.as_deref(), undoer)
+                .await
+        );
+
+        let new_mentions = AllowedMentions::default();
+        let in_place_target = self
+            .edit()
+            .edit_in_place(
+                command.channel_id,
+                guild_id,
+                (message.id, reposted),
+                (&original_content, &new_mentions),
+                false,
+            )
+            .await?;
+        let (undone, report) = if let Some(target) = in_place_target {
+            (
+                Some(target),
+                "\n\ni edited it without re-posting, so if its name shows who edited it, that \
+                 stays"
+                    .to_owned(),
+            )
+        } else {
+            let replayed = Box::pin(self.edit().replay(&edit::Replay {
+                channel_id: command.channel_id,
+                guild_id,
+                target_id: message.id,
+                target_content: &original_content,
+                edited_suffix: None,
+                reason: None,
+                audit_reason: &audit_reason,
+                out_of_order: false,
+                undoable: false,
+                new_mentions: &new_mentions,
+            }))
+            .await?;
+            let report = replayed.report();
+            (replayed.target, report)
+        };
+
+        if let Some(target) = &undone {
+            self.edit()
+                .add_version(target, undoer.id, &original_content, Some("undid the edit"))
+                .await?;
+        }
+
+        let mut response = "done! i put the message back how it was".to_owned();
+        response.push_str(&report);
+
+        self.update_response()
+            .content(&response.chars().take(2000).collect::<String>())
+            .exec()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub fn build() -> Command {
+    CommandBuilder::new("undo edit".to_owned(), "".to_owned(), CommandType::Message)
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .build()
+}
@@ -8,9 +8,11 @@
 )]
 
 mod interaction;
+mod storage;
 
 use std::{env, sync::Arc};
 
+use dashmap::DashMap;
 use futures_util::StreamExt;
 use twilight_cache_inmemory::{InMemoryCache, ResourceType};
 use twilight_gateway::{Cluster, EventTypeFlags};
@@ -22,14 +24,26 @@ use twilight_model::{
     },
     guild::Guild,
     id::{
-        marker::{ApplicationMarker, GuildMarker, UserMarker},
+        marker::{
+            ApplicationMarker, ChannelMarker, GuildMarker, InteractionMarker, MessageMarker,
+            UserMarker,
+        },
         Id,
     },
 };
 use twilight_webhook::cache::WebhooksCache;
 
+use crate::{
+    interaction::edit::Pending,
+    storage::{in_memory::InMemory, sqlite::Sqlite, Storage},
+};
+
 pub struct Context {
     http: Client,
+    reqwest: reqwest::Client,
+    storage: Box<dyn Storage>,
+    pending_edits: DashMap<Id<InteractionMarker>, Pending>,
+    replaying_channels: DashMap<Id<ChannelMarker>, Vec<Id<MessageMarker>>>,
     cache: InMemoryCache,
     webhooks_cache: WebhooksCache,
     application_id: Id<ApplicationMarker>,
@@ -46,10 +60,25 @@ impl Context {
     }
 
     async fn _handle_event(&self, event: Event) -> Result<(), anyhow::Error> {
-        if let Event::InteractionCreate(mut interaction) = event {
-            self.interaction_handler(&mut interaction.0)?
-                .handle(interaction.0)
-                .await?;
+        #[allow(clippy::wildcard_enum_match_arm)]
+        match event {
+            Event::InteractionCreate(mut interaction) => {
+                self.interaction_handler(&mut interaction.0)?
+                    .handle(interaction.0)
+                    .await?;
+            }
+            Event::MessageCreate(message) => {
+                if let Some(mut sent_ids) = self.replaying_channels.get_mut(&message.channel_id) {
+                    sent_ids.push(message.id);
+                }
+            }
+            Event::MessageDelete(message) => {
+                self.storage.remove_reposted_messages(&[message.id]).await?;
+            }
+            Event::MessageDeleteBulk(messages) => {
+                self.storage.remove_reposted_messages(&messages.ids).await?;
+            }
+            _ => {}
         }
         Ok(())
     }
@@ -137,8 +166,18 @@ async fn main() -> Result<(), anyhow::Error> {
 
     let webhooks_cache = WebhooksCache::new();
 
+    let storage: Box<dyn Storage> = if let Ok(url) = env::var("DATABASE_URL") {
+        Box::new(Sqlite::new(&url).await?)
+    } else {
+        Box::new(InMemory::new())
+    };
+
     let ctx = Arc::new(Context {
         http,
+        reqwest: reqwest::Client::new(),
+        storage,
+        pending_edits: DashMap::new(),
+        replaying_channels: DashMap::new(),
         cache,
         webhooks_cache,
         application_id,
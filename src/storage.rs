@@ -0,0 +1,165 @@
+pub mod in_memory;
+pub mod sqlite;
+
+use async_trait::async_trait;
+use twilight_model::{
+    guild::Permissions,
+    id::{
+        marker::{ChannelMarker, GenericMarker, GuildMarker, MessageMarker, UserMarker},
+        Id,
+    },
+};
+
+#[derive(Clone, Debug)]
+pub struct GuildConfig {
+    pub log_channel_id: Option<Id<ChannelMarker>>,
+    pub webhook_name: String,
+    pub edited_suffix: String,
+    pub editor_permission: Permissions,
+    pub error_message: String,
+    pub undo_window: u32,
+    pub max_replay_depth: u32,
+    pub out_of_order: bool,
+    pub new_mentions: bool,
+}
+
+impl Default for GuildConfig {
+    fn default() -> Self {
+        Self {
+            log_channel_id: None,
+            webhook_name: "any message editor".to_owned(),
+            edited_suffix: "edited by {editor}".to_owned(),
+            editor_permission: Permissions::MANAGE_MESSAGES,
+            error_message: "an error happened :( i let my developer know hopefully they'll fix \
+                            it soon!"
+                .to_owned(),
+            undo_window: 60,
+            max_replay_depth: 25,
+            out_of_order: false,
+            new_mentions: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RepostedMessage {
+    pub author_id: Id<UserMarker>,
+    pub origin_id: Id<MessageMarker>,
+    pub original_content: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MessageVersion {
+    pub author_id: Id<UserMarker>,
+    pub editor_id: Option<Id<UserMarker>>,
+    pub content: String,
+    pub reason: Option<String>,
+    pub timestamp: i64,
+}
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn guild_config(&self, guild_id: Id<GuildMarker>) -> Result<GuildConfig, anyhow::Error>;
+
+    async fn set_guild_config(
+        &self,
+        guild_id: Id<GuildMarker>,
+        config: &GuildConfig,
+    ) -> Result<(), anyhow::Error>;
+
+    async fn reposted_message(
+        &self,
+        message_id: Id<MessageMarker>,
+    ) -> Result<Option<RepostedMessage>, anyhow::Error>;
+
+    async fn set_reposted_message(
+        &self,
+        message_id: Id<MessageMarker>,
+        message: &RepostedMessage,
+    ) -> Result<(), anyhow::Error>;
+
+    async fn remove_reposted_messages(
+        &self,
+        message_ids: &[Id<MessageMarker>],
+    ) -> Result<(), anyhow::Error>;
+
+    async fn message_versions(
+        &self,
+        origin_id: Id<MessageMarker>,
+    ) -> Result<Vec<MessageVersion>, anyhow::Error>;
+
+    async fn add_message_version(
+        &self,
+        origin_id: Id<MessageMarker>,
+        version: &MessageVersion,
+    ) -> Result<(), anyhow::Error>;
+
+    async fn protected_ids(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Vec<Id<GenericMarker>>, anyhow::Error>;
+
+    async fn set_protected(
+        &self,
+        guild_id: Id<GuildMarker>,
+        id: Id<GenericMarker>,
+        protected: bool,
+    ) -> Result<(), anyhow::Error>;
+
+    async fn opted_out(
+        &self,
+        user_id: Id<UserMarker>,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<bool, anyhow::Error>;
+
+    async fn set_opted_out(
+        &self,
+        user_id: Id<UserMarker>,
+        guild_id: Option<Id<GuildMarker>>,
+        opted_out: bool,
+    ) -> Result<(), anyhow::Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::id::Id;
+
+    use super::{in_memory::InMemory, sqlite::Sqlite, Storage};
+
+    async fn opt_in_overrides_opt_out_everywhere(
+        storage: &impl Storage,
+    ) -> Result<(), anyhow::Error> {
+        let user_id = Id::new(1);
+        let guild_id = Id::new(2);
+        let other_guild_id = Id::new(3);
+
+        storage.set_opted_out(user_id, None, true).await?;
+        storage
+            .set_opted_out(user_id, Some(guild_id), false)
+            .await?;
+        assert!(!storage.opted_out(user_id, guild_id).await?);
+        assert!(storage.opted_out(user_id, other_guild_id).await?);
+
+        storage.set_opted_out(user_id, None, true).await?;
+        assert!(storage.opted_out(user_id, guild_id).await?);
+
+        storage.set_opted_out(user_id, None, false).await?;
+        storage
+            .set_opted_out(user_id, Some(other_guild_id), true)
+            .await?;
+        assert!(!storage.opted_out(user_id, guild_id).await?);
+        assert!(storage.opted_out(user_id, other_guild_id).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn in_memory_opt_out() -> Result<(), anyhow::Error> {
+        opt_in_overrides_opt_out_everywhere(&InMemory::new()).await
+    }
+
+    #[tokio::test]
+    async fn sqlite_opt_out() -> Result<(), anyhow::Error> {
+        opt_in_overrides_opt_out_everywhere(&Sqlite::new("sqlite::memory:").await?).await
+    }
+}
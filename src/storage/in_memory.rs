@@ -0,0 +1,162 @@
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use twilight_model::id::{
+    marker::{GenericMarker, GuildMarker, MessageMarker, UserMarker},
+    Id,
+};
+
+use crate::storage::{GuildConfig, MessageVersion, RepostedMessage, Storage};
+
+pub struct InMemory {
+    guild_configs: DashMap<Id<GuildMarker>, GuildConfig>,
+    reposted_messages: DashMap<Id<MessageMarker>, RepostedMessage>,
+    message_versions: DashMap<Id<MessageMarker>, Vec<MessageVersion>>,
+    protected_ids: DashMap<Id<GuildMarker>, HashSet<Id<GenericMarker>>>,
+    opt_outs: DashMap<Id<UserMarker>, HashMap<Option<Id<GuildMarker>>, bool>>,
+}
+
+impl InMemory {
+    pub fn new() -> Self {
+        Self {
+            guild_configs: DashMap::new(),
+            reposted_messages: DashMap::new(),
+            message_versions: DashMap::new(),
+            protected_ids: DashMap::new(),
+            opt_outs: DashMap::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for InMemory {
+    async fn guild_config(&self, guild_id: Id<GuildMarker>) -> Result<GuildConfig, anyhow::Error> {
+        Ok(self
+            .guild_configs
+            .get(&guild_id)
+            .map(|config| config.clone())
+            .unwrap_or_default())
+    }
+
+    async fn set_guild_config(
+        &self,
+        guild_id: Id<GuildMarker>,
+        config: &GuildConfig,
+    ) -> Result<(), anyhow::Error> {
+        self.guild_configs.insert(guild_id, config.clone());
+
+        Ok(())
+    }
+
+    async fn reposted_message(
+        &self,
+        message_id: Id<MessageMarker>,
+    ) -> Result<Option<RepostedMessage>, anyhow::Error> {
+        Ok(self
+            .reposted_messages
+            .get(&message_id)
+            .map(|message| message.clone()))
+    }
+
+    async fn set_reposted_message(
+        &self,
+        message_id: Id<MessageMarker>,
+        message: &RepostedMessage,
+    ) -> Result<(), anyhow::Error> {
+        self.reposted_messages.insert(message_id, message.clone());
+
+        Ok(())
+    }
+
+    async fn remove_reposted_messages(
+        &self,
+        message_ids: &[Id<MessageMarker>],
+    ) -> Result<(), anyhow::Error> {
+        for message_id in message_ids {
+            self.reposted_messages.remove(message_id);
+        }
+
+        Ok(())
+    }
+
+    async fn message_versions(
+        &self,
+        origin_id: Id<MessageMarker>,
+    ) -> Result<Vec<MessageVersion>, anyhow::Error> {
+        Ok(self
+            .message_versions
+            .get(&origin_id)
+            .map(|versions| versions.clone())
+            .unwrap_or_default())
+    }
+
+    async fn add_message_version(
+        &self,
+        origin_id: Id<MessageMarker>,
+        version: &MessageVersion,
+    ) -> Result<(), anyhow::Error> {
+        self.message_versions
+            .entry(origin_id)
+            .or_default()
+            .push(version.clone());
+
+        Ok(())
+    }
+
+    async fn protected_ids(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Vec<Id<GenericMarker>>, anyhow::Error> {
+        Ok(self
+            .protected_ids
+            .get(&guild_id)
+            .map(|ids| ids.iter().copied().collect())
+            .unwrap_or_default())
+    }
+
+    async fn set_protected(
+        &self,
+        guild_id: Id<GuildMarker>,
+        id: Id<GenericMarker>,
+        protected: bool,
+    ) -> Result<(), anyhow::Error> {
+        let mut ids = self.protected_ids.entry(guild_id).or_default();
+        if protected {
+            ids.insert(id);
+        } else {
+            ids.remove(&id);
+        }
+
+        Ok(())
+    }
+
+    async fn opted_out(
+        &self,
+        user_id: Id<UserMarker>,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<bool, anyhow::Error> {
+        Ok(self.opt_outs.get(&user_id).is_some_and(|opt_outs| {
+            opt_outs
+                .get(&Some(guild_id))
+                .or_else(|| opt_outs.get(&None))
+                .copied()
+                .unwrap_or_default()
+        }))
+    }
+
+    async fn set_opted_out(
+        &self,
+        user_id: Id<UserMarker>,
+        guild_id: Option<Id<GuildMarker>>,
+        opted_out: bool,
+    ) -> Result<(), anyhow::Error> {
+        let mut opt_outs = self.opt_outs.entry(user_id).or_default();
+        if guild_id.is_none() {
+            opt_outs.clear();
+        }
+        opt_outs.insert(guild_id, opted_out);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,340 @@
+use std::str::FromStr;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool},
+    FromRow,
+};
+use twilight_model::{
+    guild::Permissions,
+    id::{
+        marker::{GenericMarker, GuildMarker, MessageMarker, UserMarker},
+        Id,
+    },
+};
+
+use crate::storage::{GuildConfig, MessageVersion, RepostedMessage, Storage};
+
+pub struct Sqlite(SqlitePool);
+
+impl Sqlite {
+    pub async fn new(url: &str) -> Result<Self, anyhow::Error> {
+        let pool =
+            SqlitePool::connect_with(SqliteConnectOptions::from_str(url)?.create_if_missing(true))
+                .await?;
+        sqlx::migrate!().run(&pool).await?;
+
+        Ok(Self(pool))
+    }
+}
+
+#[derive(FromRow)]
+struct GuildConfigRow {
+    log_channel_id: Option<i64>,
+    webhook_name: String,
+    edited_suffix: String,
+    editor_permission: i64,
+    error_message: String,
+    undo_window: i64,
+    max_replay_depth: i64,
+    out_of_order: bool,
+    new_mentions: bool,
+}
+
+#[derive(FromRow)]
+struct RepostedMessageRow {
+    author_id: i64,
+    origin_id: i64,
+    original_content: Option<String>,
+}
+
+#[derive(FromRow)]
+struct MessageVersionRow {
+    author_id: i64,
+    editor_id: Option<i64>,
+    content: String,
+    reason: Option<String>,
+    timestamp: i64,
+}
+
+impl From<GuildConfigRow> for GuildConfig {
+    fn from(row: GuildConfigRow) -> Self {
+        Self {
+            log_channel_id: row.log_channel_id.and_then(from_i64),
+            webhook_name: row.webhook_name,
+            edited_suffix: row.edited_suffix,
+            editor_permission: Permissions::from_bits_truncate(u64_from_i64(row.editor_permission)),
+            error_message: row.error_message,
+            undo_window: u32::try_from(row.undo_window).unwrap_or_default(),
+            max_replay_depth: u32::try_from(row.max_replay_depth).unwrap_or_default(),
+            out_of_order: row.out_of_order,
+            new_mentions: row.new_mentions,
+        }
+    }
+}
+
+impl TryFrom<RepostedMessageRow> for RepostedMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(row: RepostedMessageRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            author_id: from_i64(row.author_id).context("reposted message author id is 0")?,
+            origin_id: from_i64(row.origin_id).context("reposted message origin id is 0")?,
+            original_content: row.original_content,
+        })
+    }
+}
+
+impl TryFrom<MessageVersionRow> for MessageVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(row: MessageVersionRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            author_id: from_i64(row.author_id).context("message version author id is 0")?,
+            editor_id: row.editor_id.and_then(from_i64),
+            content: row.content,
+            reason: row.reason,
+            timestamp: row.timestamp,
+        })
+    }
+}
+
+#[async_trait]
+impl Storage for Sqlite {
+    async fn guild_config(&self, guild_id: Id<GuildMarker>) -> Result<GuildConfig, anyhow::Error> {
+        Ok(sqlx::query_as::<_, GuildConfigRow>(
+            "SELECT log_channel_id, webhook_name, edited_suffix, editor_permission, error_message,
+            undo_window, max_replay_depth, out_of_order, new_mentions FROM guild_config WHERE guild_id = ?",
+        )
+        .bind(to_i64(guild_id))
+        .fetch_optional(&self.0)
+        .await?
+        .map(GuildConfig::from)
+        .unwrap_or_default())
+    }
+
+    async fn set_guild_config(
+        &self,
+        guild_id: Id<GuildMarker>,
+        config: &GuildConfig,
+    ) -> Result<(), anyhow::Error> {
+        sqlx::query(
+            "INSERT INTO guild_config
+            (guild_id, log_channel_id, webhook_name, edited_suffix, editor_permission, error_message,
+            undo_window, max_replay_depth, out_of_order, new_mentions)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (guild_id) DO UPDATE SET
+            log_channel_id = excluded.log_channel_id,
+            webhook_name = excluded.webhook_name,
+            edited_suffix = excluded.edited_suffix,
+            editor_permission = excluded.editor_permission,
+            error_message = excluded.error_message,
+            undo_window = excluded.undo_window,
+            max_replay_depth = excluded.max_replay_depth,
+            out_of_order = excluded.out_of_order,
+            new_mentions = excluded.new_mentions",
+        )
+        .bind(to_i64(guild_id))
+        .bind(config.log_channel_id.map(to_i64))
+        .bind(&config.webhook_name)
+        .bind(&config.edited_suffix)
+        .bind(i64_from_u64(config.editor_permission.bits()))
+        .bind(&config.error_message)
+        .bind(config.undo_window)
+        .bind(config.max_replay_depth)
+        .bind(config.out_of_order)
+        .bind(config.new_mentions)
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn reposted_message(
+        &self,
+        message_id: Id<MessageMarker>,
+    ) -> Result<Option<RepostedMessage>, anyhow::Error> {
+        sqlx::query_as::<_, RepostedMessageRow>(
+            "SELECT author_id, origin_id, original_content FROM reposted_message
+            WHERE message_id = ?",
+        )
+        .bind(to_i64(message_id))
+        .fetch_optional(&self.0)
+        .await?
+        .map(RepostedMessage::try_from)
+        .transpose()
+    }
+
+    async fn set_reposted_message(
+        &self,
+        message_id: Id<MessageMarker>,
+        message: &RepostedMessage,
+    ) -> Result<(), anyhow::Error> {
+        sqlx::query(
+            "INSERT INTO reposted_message (message_id, author_id, origin_id, original_content)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (message_id) DO UPDATE SET
+            author_id = excluded.author_id,
+            origin_id = excluded.origin_id,
+            original_content = excluded.original_content",
+        )
+        .bind(to_i64(message_id))
+        .bind(to_i64(message.author_id))
+        .bind(to_i64(message.origin_id))
+        .bind(&message.original_content)
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn remove_reposted_messages(
+        &self,
+        message_ids: &[Id<MessageMarker>],
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.0.begin().await?;
+        for message_id in message_ids {
+            sqlx::query("DELETE FROM reposted_message WHERE message_id = ?")
+                .bind(to_i64(*message_id))
+                .execute(&mut transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn message_versions(
+        &self,
+        origin_id: Id<MessageMarker>,
+    ) -> Result<Vec<MessageVersion>, anyhow::Error> {
+        sqlx::query_as::<_, MessageVersionRow>(
+            "SELECT author_id, editor_id, content, reason, timestamp FROM message_version
+            WHERE origin_id = ? ORDER BY rowid",
+        )
+        .bind(to_i64(origin_id))
+        .fetch_all(&self.0)
+        .await?
+        .into_iter()
+        .map(MessageVersion::try_from)
+        .collect()
+    }
+
+    async fn add_message_version(
+        &self,
+        origin_id: Id<MessageMarker>,
+        version: &MessageVersion,
+    ) -> Result<(), anyhow::Error> {
+        sqlx::query(
+            "INSERT INTO message_version (origin_id, author_id, editor_id, content, reason, timestamp)
+            VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(to_i64(origin_id))
+        .bind(to_i64(version.author_id))
+        .bind(version.editor_id.map(to_i64))
+        .bind(&version.content)
+        .bind(&version.reason)
+        .bind(version.timestamp)
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn protected_ids(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Vec<Id<GenericMarker>>, anyhow::Error> {
+        Ok(
+            sqlx::query_as::<_, (i64,)>("SELECT id FROM protected WHERE guild_id = ?")
+                .bind(to_i64(guild_id))
+                .fetch_all(&self.0)
+                .await?
+                .into_iter()
+                .filter_map(|(id,)| from_i64(id))
+                .collect(),
+        )
+    }
+
+    async fn set_protected(
+        &self,
+        guild_id: Id<GuildMarker>,
+        id: Id<GenericMarker>,
+        protected: bool,
+    ) -> Result<(), anyhow::Error> {
+        let query = if protected {
+            "INSERT INTO protected (guild_id, id) VALUES (?, ?) ON CONFLICT DO NOTHING"
+        } else {
+            "DELETE FROM protected WHERE guild_id = ? AND id = ?"
+        };
+        sqlx::query(query)
+            .bind(to_i64(guild_id))
+            .bind(to_i64(id))
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn opted_out(
+        &self,
+        user_id: Id<UserMarker>,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<bool, anyhow::Error> {
+        Ok(sqlx::query_as::<_, (bool,)>(
+            "SELECT opted_out FROM opt_out WHERE user_id = ? AND guild_id IN (0, ?)
+            ORDER BY guild_id DESC",
+        )
+        .bind(to_i64(user_id))
+        .bind(to_i64(guild_id))
+        .fetch_optional(&self.0)
+        .await?
+        .is_some_and(|(opted_out,)| opted_out))
+    }
+
+    async fn set_opted_out(
+        &self,
+        user_id: Id<UserMarker>,
+        guild_id: Option<Id<GuildMarker>>,
+        opted_out: bool,
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.0.begin().await?;
+        if guild_id.is_none() {
+            sqlx::query("DELETE FROM opt_out WHERE user_id = ?")
+                .bind(to_i64(user_id))
+                .execute(&mut transaction)
+                .await?;
+        }
+        sqlx::query(
+            "INSERT INTO opt_out (user_id, guild_id, opted_out) VALUES (?, ?, ?)
+            ON CONFLICT (user_id, guild_id) DO UPDATE SET opted_out = excluded.opted_out",
+        )
+        .bind(to_i64(user_id))
+        .bind(guild_id.map_or(0, to_i64))
+        .bind(opted_out)
+        .execute(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
+
+const fn to_i64<T>(id: Id<T>) -> i64 {
+    i64_from_u64(id.get())
+}
+
+const fn from_i64<T>(id: i64) -> Option<Id<T>> {
+    Id::new_checked(u64_from_i64(id))
+}
+
+#[allow(clippy::as_conversions, clippy::cast_possible_wrap)]
+const fn i64_from_u64(value: u64) -> i64 {
+    value as i64
+}
+
+#[allow(clippy::as_conversions, clippy::cast_sign_loss)]
+const fn u64_from_i64(value: i64) -> u64 {
+    value as u64
+}